@@ -4,7 +4,13 @@ mod utils;
 
 pub use opts::{
     Opts, Subcommand, base64::Base64DecodeOpts, base64::Base64EncodeOpts, base64::Base64SubCommand,
-    http::HttpServeOpts, http::HttpSubCommand, text::TextSubCommand,
+    codec::DecodeOpts, codec::EncodeOpts, convert::ConvertOpts, hash::HashOpts,
+    hexdump::HexdumpOpts, hmac::HmacSubCommand, http::HttpServeOpts, http::HttpSubCommand,
+    jwt::JwtSubCommand, text::TextSubCommand,
+};
+pub use process::{
+    process_base64, process_convert, process_decode, process_encode, process_gen_pass,
+    process_hash, process_hexdump, process_hmac, process_http_serve, process_jwt, process_text,
+    to_json_file,
 };
-pub use process::{process_base64, process_gen_pass, process_http_serve, process_text, to_json_file};
 pub use utils::get_reader;
@@ -1,7 +1,8 @@
 use clap::Parser;
 use rcli::{
-    Opts, Subcommand, process_base64, process_gen_pass, process_http_serve, process_text,
-    to_json_file,
+    Opts, Subcommand, process_base64, process_convert, process_decode, process_encode,
+    process_gen_pass, process_hash, process_hexdump, process_hmac, process_http_serve, process_jwt,
+    process_text, to_json_file,
 };
 
 #[tokio::main]
@@ -11,12 +12,12 @@ async fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
     match opts.cmd {
         Subcommand::Csv(opts) => {
-            let output = if let Some(output) = opts.output {
+            let output = if let Some(output) = &opts.output {
                 output.clone()
             } else {
-                format!("output.{}", opts.format)
+                format!("output.{}", opts.format.extension())
             };
-            to_json_file(&opts.input, output, opts.format)?;
+            to_json_file(&opts, &output)?;
         }
         Subcommand::GenPass(opts) => {
             process_gen_pass(&opts)?;
@@ -28,6 +29,13 @@ async fn main() -> anyhow::Result<()> {
             process_text(&sum_cmd)?;
         }
         Subcommand::Http(cmd) => process_http_serve(cmd).await?,
+        Subcommand::Jwt(cmd) => process_jwt(&cmd)?,
+        Subcommand::Hash(opts) => process_hash(&opts)?,
+        Subcommand::Hmac(cmd) => process_hmac(&cmd)?,
+        Subcommand::Encode(opts) => process_encode(&opts)?,
+        Subcommand::Decode(opts) => process_decode(&opts)?,
+        Subcommand::Hexdump(opts) => process_hexdump(&opts)?,
+        Subcommand::Convert(opts) => process_convert(&opts)?,
     }
     Ok(())
 }
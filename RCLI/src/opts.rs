@@ -1,13 +1,24 @@
 pub mod base64;
+pub mod codec;
+pub mod convert;
 pub mod csv;
 pub mod gen_pass;
+pub mod hash;
+pub mod hexdump;
+pub mod hmac;
 pub mod http;
+pub mod jwt;
 pub mod text;
 
 use crate::opts::base64::Base64SubCommand;
+use crate::opts::codec::{DecodeOpts, EncodeOpts};
+use crate::opts::convert::ConvertOpts;
 use crate::opts::csv::CsvOpts;
 use crate::opts::gen_pass::GenPassOpts;
-use crate::{HttpSubCommand, TextSubCommand};
+use crate::opts::hash::HashOpts;
+use crate::opts::hexdump::HexdumpOpts;
+use crate::opts::hmac::HmacSubCommand;
+use crate::{HttpSubCommand, JwtSubCommand, TextSubCommand};
 use clap::Parser;
 use std::path::PathBuf;
 
@@ -33,6 +44,27 @@ pub enum Subcommand {
 
     #[command(subcommand, about = "启动文件服务")]
     Http(HttpSubCommand),
+
+    #[command(subcommand, about = "JWT签名与验证")]
+    Jwt(JwtSubCommand),
+
+    #[command(name = "hash", about = "计算文件哈希或校验哈希列表")]
+    Hash(HashOpts),
+
+    #[command(subcommand, about = "HMAC签名与验证")]
+    Hmac(HmacSubCommand),
+
+    #[command(name = "encode", about = "按指定编码方式编码")]
+    Encode(EncodeOpts),
+
+    #[command(name = "decode", about = "按指定编码方式解码")]
+    Decode(DecodeOpts),
+
+    #[command(name = "hexdump", about = "以十六进制查看二进制内容")]
+    Hexdump(HexdumpOpts),
+
+    #[command(name = "convert", about = "在CSV、JSON、NDJSON、YAML等格式之间转换")]
+    Convert(ConvertOpts),
 }
 
 /// 自定义的参数校验函数。用于校验输入文件是否存在
@@ -1,5 +1,6 @@
 use clap::{Parser, ValueEnum};
 use std::fmt::Display;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(Debug, Parser)]
@@ -18,6 +19,18 @@ pub struct Base64EncodeOpts {
 
     #[arg(short, long, default_value = "standard")]
     pub format: Base64Format,
+
+    /// 输出时补齐 "="。默认 standard 补齐，url-safe 不补齐
+    #[arg(long, overrides_with = "no_pad")]
+    pub pad: bool,
+
+    /// 输出时不补齐 "="
+    #[arg(long, overrides_with = "pad")]
+    pub no_pad: bool,
+
+    /// 按指定列数换行，只写 --wrap 时按 MIME 的 76 列换行
+    #[arg(short, long, num_args = 0..=1, default_missing_value = "76")]
+    pub wrap: Option<usize>,
 }
 
 #[derive(Debug, Parser)]
@@ -25,8 +38,22 @@ pub struct Base64DecodeOpts {
     /// 需要解码的内容
     #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
     pub input: String,
+
+    /// auto 会自动识别字母表与补齐，并在stderr输出识别结果
     #[arg(short, long, default_value = "standard")]
-    pub format: Base64Format,
+    pub format: Base64DecodeFormat,
+
+    /// 只接受带 "=" 补齐的输入。默认两种都接受
+    #[arg(long, overrides_with = "no_pad")]
+    pub pad: bool,
+
+    /// 只接受不带 "=" 补齐的输入
+    #[arg(long, overrides_with = "pad")]
+    pub no_pad: bool,
+
+    /// 解码结果的输出文件，不指定则输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -60,4 +87,39 @@ impl Display for Base64Format {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", Into::<&str>::into(*self))
     }
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Base64DecodeFormat {
+    Standard,
+    UrlSafe,
+    Auto,
+}
+
+impl FromStr for Base64DecodeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "standard" => Ok(Base64DecodeFormat::Standard),
+            "url-safe" => Ok(Base64DecodeFormat::UrlSafe),
+            "auto" => Ok(Base64DecodeFormat::Auto),
+            _ => Err(anyhow::anyhow!("无效的格式化 format: {}", s)),
+        }
+    }
+}
+
+impl From<Base64DecodeFormat> for &'static str {
+    fn from(value: Base64DecodeFormat) -> Self {
+        match value {
+            Base64DecodeFormat::Standard => "standard",
+            Base64DecodeFormat::UrlSafe => "url-safe",
+            Base64DecodeFormat::Auto => "auto",
+        }
+    }
+}
+
+impl Display for Base64DecodeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
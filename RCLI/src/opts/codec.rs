@@ -0,0 +1,80 @@
+use clap::{Parser, ValueEnum};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+pub struct EncodeOpts {
+    /// 需要编码的内容
+    #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
+    pub input: String,
+
+    /// 编码方式。base58 和 ascii85 需要把全部输入读入内存，其他编码分块处理
+    #[arg(short, long, default_value = "base64")]
+    pub codec: Codec,
+}
+
+#[derive(Debug, Parser)]
+pub struct DecodeOpts {
+    /// 需要解码的内容
+    #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
+    pub input: String,
+
+    /// 编码方式。base58 和 ascii85 需要把全部输入读入内存，其他编码分块处理
+    #[arg(short, long, default_value = "base64")]
+    pub codec: Codec,
+
+    /// 解码结果的输出文件，不指定则输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Codec {
+    Base64,
+    #[value(name = "base64url")]
+    Base64Url,
+    Base32,
+    #[value(name = "base32hex")]
+    Base32Hex,
+    Base58,
+    Hex,
+    Ascii85,
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "base64" => Ok(Codec::Base64),
+            "base64url" => Ok(Codec::Base64Url),
+            "base32" => Ok(Codec::Base32),
+            "base32hex" => Ok(Codec::Base32Hex),
+            "base58" => Ok(Codec::Base58),
+            "hex" => Ok(Codec::Hex),
+            "ascii85" => Ok(Codec::Ascii85),
+            _ => Err(anyhow::anyhow!("无效的编码方式 codec: {}", s)),
+        }
+    }
+}
+
+impl From<Codec> for &'static str {
+    fn from(value: Codec) -> Self {
+        match value {
+            Codec::Base64 => "base64",
+            Codec::Base64Url => "base64url",
+            Codec::Base32 => "base32",
+            Codec::Base32Hex => "base32hex",
+            Codec::Base58 => "base58",
+            Codec::Hex => "hex",
+            Codec::Ascii85 => "ascii85",
+        }
+    }
+}
+
+impl Display for Codec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::opts::csv::{InputFormat, OutputFormat, parse_delimiter};
+use clap::{Parser, ValueEnum};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// 在 CSV、JSON、NDJSON、YAML 等格式之间转换
+#[derive(Debug, Parser)]
+pub struct ConvertOpts {
+    /// 输入文件路径
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub input: String,
+
+    /// 输出文件路径，不指定则输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// 输入格式。CSV 和 NDJSON 逐条读取，JSON/YAML 需要整体读入内存
+    #[arg(long, default_value = "json")]
+    pub from: InputFormat,
+
+    /// 输出格式
+    #[arg(long, default_value = "csv")]
+    pub to: OutputFormat,
+
+    /// 读写CSV时使用的分隔符，制表符可以写成 "\t" 或 "tab"
+    #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+    pub delimiter: u8,
+
+    /// 输出CSV时数组的处理方式。join 合并成一个单元格，explode 每个元素输出一行
+    #[arg(long, default_value = "join")]
+    pub arrays: ArrayMode,
+
+    /// join 方式下数组元素之间的分隔符
+    #[arg(long, default_value = ";")]
+    pub separator: String,
+
+    /// 输出CSV时的列，用逗号分隔，嵌套字段写成 "a.b"。不指定时需要先读取全部记录来确定列，指定后逐条写出，不在列中的字段会被忽略
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ArrayMode {
+    Join,
+    Explode,
+}
+
+impl FromStr for ArrayMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "join" => Ok(ArrayMode::Join),
+            "explode" => Ok(ArrayMode::Explode),
+            _ => Err(anyhow::anyhow!("无效的数组处理方式: {}", s)),
+        }
+    }
+}
+
+impl From<ArrayMode> for &'static str {
+    fn from(value: ArrayMode) -> Self {
+        match value {
+            ArrayMode::Join => "join",
+            ArrayMode::Explode => "explode",
+        }
+    }
+}
+
+impl Display for ArrayMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
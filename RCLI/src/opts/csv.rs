@@ -17,26 +17,73 @@ pub struct CsvOpts {
     #[arg(short, long, default_value = "json")]
     pub format: OutputFormat,
 
-    /// 分隔符
-    #[arg(short, long, default_value_t = ',')]
-    pub delimiter: char,
+    /// 分隔符，制表符可以写成 "\t" 或 "tab"
+    #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+    pub delimiter: u8,
 
-    /// CSV文件是否有头
-    #[arg(long, default_value_t = true)] // default_value_t 这个是直接赋值
+    /// CSV文件是否有头，没有头时用 --header false
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    // default_value_t 这个是直接赋值
     pub header: bool,
+
+    /// 自定义列名，用逗号分隔。没有头时默认为 col1..colN，有头时会替换原来的列名
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Option<Vec<String>>,
+
+    /// 不推断类型，所有值都输出为字符串
+    #[arg(long)]
+    pub no_infer: bool,
+
+    /// 推断类型时识别日期，并统一成 YYYY-MM-DD 格式。RFC3339 时间保持原文不变
+    #[arg(long, conflicts_with = "no_infer")]
+    pub infer_dates: bool,
+
+    /// 指定列类型的schema文件(JSON/YAML)，例如 {"Phone": "string", "Score": "float"}。
+    /// 可用的类型: string, integer, float, bool, date
+    #[arg(long, value_parser = crate::opts::verify_file)]
+    pub schema: Option<String>,
+}
+
+/// 分隔符必须是单个ASCII字符
+pub(crate) fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s {
+        "\\t" | "tab" => Ok(b'\t'),
+        s if s.len() == 1 && s.is_ascii() => Ok(s.as_bytes()[0]),
+        _ => Err(format!("分隔符必须是单个ASCII字符: {}", s)),
+    }
 }
 
 #[derive(Debug, Parser, Copy, Clone, ValueEnum)]
 pub enum OutputFormat {
+    Csv,
     Json,
     Yaml,
+    Ndjson,
+    Toml,
+    Xml,
+    Markdown,
+}
+
+impl OutputFormat {
+    /// 未指定输出文件时使用的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            _ => (*self).into(),
+        }
+    }
 }
 
 impl From<OutputFormat> for &'static str {
     fn from(value: OutputFormat) -> Self {
         match value {
+            OutputFormat::Csv => "csv",
             OutputFormat::Json => "json",
             OutputFormat::Yaml => "yaml",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Xml => "xml",
+            OutputFormat::Markdown => "markdown",
         }
     }
 }
@@ -47,8 +94,13 @@ impl FromStr for OutputFormat {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
             "json" => Ok(OutputFormat::Json),
             "yaml" => Ok(OutputFormat::Yaml),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "toml" => Ok(OutputFormat::Toml),
+            "xml" => Ok(OutputFormat::Xml),
+            "markdown" => Ok(OutputFormat::Markdown),
             _ => Err(anyhow::anyhow!("无效的format类型")),
         }
     }
@@ -61,3 +113,65 @@ impl Display for OutputFormat {
         write!(f, "{}", Into::<&str>::into(*self))
     }
 }
+
+/// rcli convert 支持读取的格式
+#[derive(Debug, Parser, Copy, Clone, ValueEnum)]
+pub enum InputFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Yaml,
+}
+
+impl From<InputFormat> for &'static str {
+    fn from(value: InputFormat) -> Self {
+        match value {
+            InputFormat::Csv => "csv",
+            InputFormat::Json => "json",
+            InputFormat::Ndjson => "ndjson",
+            InputFormat::Yaml => "yaml",
+        }
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(InputFormat::Csv),
+            "json" => Ok(InputFormat::Json),
+            "ndjson" => Ok(InputFormat::Ndjson),
+            "yaml" => Ok(InputFormat::Yaml),
+            _ => Err(anyhow::anyhow!("无效的format类型")),
+        }
+    }
+}
+
+impl Display for InputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&str>::into(*self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(";"), Ok(b';'));
+        assert_eq!(parse_delimiter("\\t"), Ok(b'\t'));
+        assert_eq!(parse_delimiter("tab"), Ok(b'\t'));
+        assert!(parse_delimiter("；").is_err());
+        assert!(parse_delimiter(",,").is_err());
+    }
+
+    #[test]
+    fn test_header_toggle() {
+        let opts = CsvOpts::try_parse_from(["csv", "-i", "-", "--header", "false"]).unwrap();
+        assert!(!opts.header);
+        let opts = CsvOpts::try_parse_from(["csv", "-i", "-"]).unwrap();
+        assert!(opts.header);
+    }
+}
@@ -0,0 +1,101 @@
+use clap::{Parser, ValueEnum};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// 计算文件哈希的参数
+#[derive(Debug, Parser)]
+pub struct HashOpts {
+    /// 需要计算哈希的内容
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub input: String,
+
+    /// 哈希算法
+    #[arg(short, long, default_value = "sha256")]
+    pub algo: HashAlgorithm,
+
+    /// 哈希的输出格式
+    #[arg(short, long, default_value = "hex")]
+    pub format: HashOutputFormat,
+
+    /// 校验 sha256sum 等工具生成的哈希列表文件
+    #[arg(short, long, conflicts_with = "input", value_parser = crate::opts::verify_file)]
+    pub check: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HashAlgorithm {
+    Blake3,
+    Sha256,
+    Sha512,
+    Sha1,
+    Md5,
+    Crc32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HashOutputFormat {
+    Hex,
+    Base64,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "md5" => Ok(HashAlgorithm::Md5),
+            "crc32" => Ok(HashAlgorithm::Crc32),
+            _ => Err(anyhow::anyhow!("无效的哈希算法: {}", s)),
+        }
+    }
+}
+
+impl From<HashAlgorithm> for &'static str {
+    fn from(value: HashAlgorithm) -> Self {
+        match value {
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Crc32 => "crc32",
+        }
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+impl FromStr for HashOutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hex" => Ok(HashOutputFormat::Hex),
+            "base64" => Ok(HashOutputFormat::Base64),
+            _ => Err(anyhow::anyhow!("无效的输出格式: {}", s)),
+        }
+    }
+}
+
+impl From<HashOutputFormat> for &'static str {
+    fn from(value: HashOutputFormat) -> Self {
+        match value {
+            HashOutputFormat::Hex => "hex",
+            HashOutputFormat::Base64 => "base64",
+        }
+    }
+}
+
+impl Display for HashOutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
@@ -0,0 +1,62 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+/// 以 xxd 的格式输出十六进制内容
+#[derive(Debug, Parser)]
+pub struct HexdumpOpts {
+    /// 需要查看的内容
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub input: String,
+
+    /// 从第几个字节开始，支持 0x 开头的十六进制
+    #[arg(short = 's', long, default_value = "0", value_parser = parse_number)]
+    pub offset: u64,
+
+    /// 最多输出多少个字节，支持 0x 开头的十六进制
+    #[arg(short = 'n', long, value_parser = parse_number)]
+    pub length: Option<u64>,
+
+    /// 每行输出的字节数
+    #[arg(short, long, default_value = "16", value_parser = parse_width)]
+    pub cols: usize,
+
+    /// 每组的字节数
+    #[arg(short, long, default_value = "2", value_parser = parse_width)]
+    pub group: usize,
+
+    /// 把 xxd 格式的内容还原成二进制，行之间的空缺用 0 填充，最多 16MiB
+    #[arg(short, long, conflicts_with_all = ["offset", "length", "cols", "group"])]
+    pub reverse: bool,
+
+    /// 输出文件，不指定则输出到标准输出
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+fn parse_number(s: &str) -> Result<u64, String> {
+    let result = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    result.map_err(|_| format!("无效的数字: {}", s))
+}
+
+fn parse_width(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(width @ 1..=256) => Ok(width),
+        _ => Err(format!("需要是 1 到 256 之间的数字: {}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1024"), Ok(1024));
+        assert_eq!(parse_number("0x400"), Ok(1024));
+        assert!(parse_number("0xzz").is_err());
+        assert!(parse_number("-1").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::opts::hash::HashOutputFormat;
+use clap::{Parser, ValueEnum};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+pub enum HmacSubCommand {
+    #[command(about = "计算HMAC")]
+    Sign(HmacSignOpts),
+    #[command(about = "验证HMAC，例如第三方服务推送的webhook签名")]
+    Verify(HmacVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct HmacSignOpts {
+    /// 需要计算HMAC的内容
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub input: String,
+
+    /// 直接在命令行中给出密钥。会出现在进程列表和shell历史中，建议使用 --key-file 或 --key-env
+    #[arg(short, long, required_unless_present_any = ["key_file", "key_env"], conflicts_with_all = ["key_file", "key_env"])]
+    pub key: Option<String>,
+
+    /// 从文件读取密钥，"-" 表示stdin。raw格式时会去掉末尾的换行
+    #[arg(long, conflicts_with = "key_env", value_parser = crate::opts::verify_file)]
+    pub key_file: Option<String>,
+
+    /// 从指定的环境变量读取密钥
+    #[arg(long, value_name = "ENV")]
+    pub key_env: Option<String>,
+
+    /// 密钥的编码格式
+    #[arg(long, default_value = "raw")]
+    pub key_format: HmacKeyFormat,
+
+    /// 摘要算法
+    #[arg(short, long, default_value = "sha256")]
+    pub alg: HmacAlgorithm,
+
+    /// HMAC的输出格式
+    #[arg(short, long, default_value = "hex")]
+    pub format: HashOutputFormat,
+}
+
+#[derive(Debug, Parser)]
+pub struct HmacVerifyOpts {
+    /// 需要验证的内容
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub input: String,
+
+    /// 直接在命令行中给出密钥。会出现在进程列表和shell历史中，建议使用 --key-file 或 --key-env
+    #[arg(short, long, required_unless_present_any = ["key_file", "key_env"], conflicts_with_all = ["key_file", "key_env"])]
+    pub key: Option<String>,
+
+    /// 从文件读取密钥，"-" 表示stdin。raw格式时会去掉末尾的换行
+    #[arg(long, conflicts_with = "key_env", value_parser = crate::opts::verify_file)]
+    pub key_file: Option<String>,
+
+    /// 从指定的环境变量读取密钥
+    #[arg(long, value_name = "ENV")]
+    pub key_env: Option<String>,
+
+    /// 密钥的编码格式
+    #[arg(long, default_value = "raw")]
+    pub key_format: HmacKeyFormat,
+
+    /// 摘要算法
+    #[arg(short, long, default_value = "sha256")]
+    pub alg: HmacAlgorithm,
+
+    /// 期望的HMAC，允许带有 "sha256=" 这类前缀
+    #[arg(short, long)]
+    pub sig: String,
+
+    /// HMAC的编码格式
+    #[arg(short, long, default_value = "hex")]
+    pub format: HashOutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HmacAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HmacKeyFormat {
+    Raw,
+    Hex,
+    Base64,
+}
+
+impl FromStr for HmacAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha256" => Ok(HmacAlgorithm::Sha256),
+            "sha512" => Ok(HmacAlgorithm::Sha512),
+            _ => Err(anyhow::anyhow!("无效的HMAC算法: {}", s)),
+        }
+    }
+}
+
+impl From<HmacAlgorithm> for &'static str {
+    fn from(value: HmacAlgorithm) -> Self {
+        match value {
+            HmacAlgorithm::Sha256 => "sha256",
+            HmacAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+impl Display for HmacAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+impl FromStr for HmacKeyFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(HmacKeyFormat::Raw),
+            "hex" => Ok(HmacKeyFormat::Hex),
+            "base64" => Ok(HmacKeyFormat::Base64),
+            _ => Err(anyhow::anyhow!("无效的密钥格式: {}", s)),
+        }
+    }
+}
+
+impl From<HmacKeyFormat> for &'static str {
+    fn from(value: HmacKeyFormat) -> Self {
+        match value {
+            HmacKeyFormat::Raw => "raw",
+            HmacKeyFormat::Hex => "hex",
+            HmacKeyFormat::Base64 => "base64",
+        }
+    }
+}
+
+impl Display for HmacKeyFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
@@ -0,0 +1,121 @@
+use clap::{Parser, ValueEnum};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+pub enum JwtSubCommand {
+    #[command(about = "生成JWT")]
+    Sign(JwtSignOpts),
+    #[command(about = "验证JWT并输出claims")]
+    Verify(JwtVerifyOpts),
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtSignOpts {
+    /// 主题(sub)
+    #[arg(long)]
+    pub sub: String,
+
+    /// 受众(aud)
+    #[arg(long)]
+    pub aud: String,
+
+    /// 有效期，支持 s/m/h/d/w 单位，例如 30m、14d，不带单位时为秒
+    #[arg(long, default_value = "1d", value_parser = parse_duration)]
+    pub exp: u64,
+
+    /// 密钥文件。HS256为任意字节的密钥，EdDSA为Ed25519私钥
+    #[arg(short, long, value_parser = crate::opts::verify_file)]
+    pub key: String,
+
+    #[arg(long, default_value = "hs256")]
+    pub alg: JwtAlgorithm,
+}
+
+#[derive(Debug, Parser)]
+pub struct JwtVerifyOpts {
+    /// 需要验证的token
+    #[arg(short, long, default_value = "-", value_parser = crate::opts::verify_file)]
+    pub token: String,
+
+    /// 密钥文件。HS256为签名时使用的密钥，EdDSA为Ed25519公钥
+    #[arg(short, long, value_parser = crate::opts::verify_file)]
+    pub key: String,
+
+    /// 期望的受众(aud)，不指定则不校验
+    #[arg(long)]
+    pub aud: Option<String>,
+
+    /// 只接受该算法签名的token
+    #[arg(long, default_value = "hs256")]
+    pub alg: JwtAlgorithm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum JwtAlgorithm {
+    Hs256,
+    #[value(name = "eddsa")]
+    EdDsa,
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hs256" => Ok(JwtAlgorithm::Hs256),
+            "eddsa" => Ok(JwtAlgorithm::EdDsa),
+            _ => Err(anyhow::anyhow!("无效的算法: {}", s)),
+        }
+    }
+}
+
+// 与JWT header中alg字段的写法一致
+impl From<JwtAlgorithm> for &'static str {
+    fn from(value: JwtAlgorithm) -> Self {
+        match value {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+}
+
+impl Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Into::<&'static str>::into(*self))
+    }
+}
+
+/// 解析有效期，返回秒数
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, "s"),
+    };
+    let num: u64 = num.parse().map_err(|_| format!("无效的有效期: {}", s))?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("无效的有效期单位: {}", unit)),
+    };
+    num.checked_mul(unit)
+        .ok_or_else(|| format!("有效期过长: {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("3600"), Ok(3600));
+        assert_eq!(parse_duration("30m"), Ok(30 * 60));
+        assert_eq!(parse_duration("14d"), Ok(14 * 24 * 60 * 60));
+        assert!(parse_duration("1y").is_err());
+        assert!(parse_duration("d").is_err());
+    }
+}
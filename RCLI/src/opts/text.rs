@@ -1,5 +1,8 @@
+use crate::opts::base64::Base64Format;
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 use std::str::FromStr;
 
 #[derive(Debug, Parser)]
@@ -8,6 +11,12 @@ pub enum TextSubCommand {
     Sign(TextSignOpts),
     #[command(about = "验证消息")]
     Verify(TextVerifyOpts),
+    #[command(about = "生成签名所需的密钥")]
+    Generate(TextKeyGenerateOpts),
+    #[command(about = "使用ChaCha20-Poly1305加密消息")]
+    Encrypt(TextEncryptOpts),
+    #[command(about = "使用ChaCha20-Poly1305解密消息")]
+    Decrypt(TextDecryptOpts),
 }
 
 #[derive(Debug, Parser)]
@@ -20,26 +29,123 @@ pub struct TextSignOpts {
     #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
     pub key: String,
 
-    #[arg(long, default_value = "black3")]
+    #[arg(long, default_value = "blake3")]
     pub format: TextSignFormat,
+
+    /// 将签名及其元数据写入分离的签名文件(json)，不指定则只输出签名
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// 对整个目录生成签名清单，清单中记录每个文件的blake3哈希
+    #[arg(long, conflicts_with = "input", value_parser=crate::opts::verify_path)]
+    pub manifest: Option<PathBuf>,
+    /// SSH签名的namespace，与 ssh-keygen -Y sign -n 相同
+    #[arg(short, long, default_value = "file")]
+    pub namespace: String,
 }
 
 #[derive(Debug, Parser)]
 pub struct TextVerifyOpts {
     #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file, help = "需要解码的内容")]
     pub input: String,
-    #[arg(short, long, help = "key")]
-    pub key: String,
-    #[arg(short, long, help = "签名")]
-    pub sig: String,
-    #[arg(long, default_value = "black3", help = "格式")]
+    #[arg(short, long, required_unless_present = "allowed_signers", help = "key")]
+    pub key: Option<String>,
+    #[arg(
+        short,
+        long,
+        required_unless_present = "sig_file",
+        allow_hyphen_values = true,
+        help = "签名"
+    )]
+    pub sig: Option<String>,
+    #[arg(long, conflicts_with = "sig", value_parser=crate::opts::verify_file, help = "`text sign --output` 生成的签名文件，文件中记录的签名格式必须与 --format 一致")]
+    pub sig_file: Option<String>,
+    #[arg(
+        long,
+        help = "格式，默认为blake3。使用 --sig-file 时必须显式指定，不会采用签名文件中记录的格式"
+    )]
+    pub format: Option<TextSignFormat>,
+    #[arg(long, conflicts_with = "input", requires = "sig_file", value_parser=crate::opts::verify_path, help = "按 --sig-file 指定的清单校验目录，报告新增、缺失和修改的文件")]
+    pub manifest: Option<PathBuf>,
+    #[arg(short, long, default_value = "file", help = "SSH签名的namespace")]
+    pub namespace: String,
+    #[arg(long, value_parser=crate::opts::verify_file, help = "ssh-keygen 格式的 allowed_signers 文件，用于确认SSH签名的签名者")]
+    pub allowed_signers: Option<String>,
+    #[arg(
+        long,
+        requires = "allowed_signers",
+        help = "签名者身份，需要与 allowed_signers 中的principals匹配"
+    )]
+    pub identity: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextKeyGenerateOpts {
+    /// 密钥格式
+    #[arg(long, default_value = "blake3")]
     pub format: TextSignFormat,
+
+    /// 密钥文件的输出目录
+    #[arg(short, long, value_parser=crate::opts::verify_path)]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextEncryptOpts {
+    /// 需要加密的内容
+    #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
+    pub input: String,
+
+    /// 32字节的密钥文件，可以使用 `text generate --format blake3` 生成
+    #[arg(short, long, value_parser=crate::opts::verify_file, required_unless_present_any = ["password", "password_env"])]
+    pub key: Option<String>,
+
+    /// 使用密码代替密钥文件，密码从终端输入
+    #[arg(long, conflicts_with_all = ["key", "password_env"])]
+    pub password: bool,
+
+    /// 使用密码代替密钥文件，密码从指定的环境变量中读取
+    #[arg(long, value_name = "ENV", conflicts_with = "key")]
+    pub password_env: Option<String>,
+
+    /// 密文输出的base64格式
+    #[arg(long, default_value = "standard")]
+    pub format: Base64Format,
+}
+
+#[derive(Debug, Parser)]
+pub struct TextDecryptOpts {
+    /// 需要解密的base64密文
+    #[arg(short, long, default_value = "-", value_parser=crate::opts::verify_file)]
+    pub input: String,
+
+    /// 32字节的密钥文件
+    #[arg(short, long, value_parser=crate::opts::verify_file, required_unless_present_any = ["password", "password_env"])]
+    pub key: Option<String>,
+
+    /// 使用密码代替密钥文件，密码从终端输入
+    #[arg(long, conflicts_with_all = ["key", "password_env"])]
+    pub password: bool,
+
+    /// 使用密码代替密钥文件，密码从指定的环境变量中读取
+    #[arg(long, value_name = "ENV", conflicts_with = "key")]
+    pub password_env: Option<String>,
+
+    /// 密文的base64格式
+    #[arg(long, default_value = "standard")]
+    pub format: Base64Format,
 }
 
-#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TextSignFormat {
     Blake3,
     Ed25519,
+    /// 预哈希的Ed25519，对大文件签名时内存占用恒定
+    Ed25519ph,
+    /// 与 ssh-keygen -Y 兼容的 SSHSIG 签名
+    #[value(name = "sshsig")]
+    SshSig,
 }
 
 // 将 &str转成enum
@@ -47,8 +153,10 @@ impl FromStr for TextSignFormat {
     type Err = anyhow::Error;
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value.to_lowercase().as_str() {
-            "black3" => Ok(TextSignFormat::Blake3),
+            "blake3" => Ok(TextSignFormat::Blake3),
             "ed25519" => Ok(TextSignFormat::Ed25519),
+            "ed25519ph" => Ok(TextSignFormat::Ed25519ph),
+            "sshsig" => Ok(TextSignFormat::SshSig),
             _ => Err(anyhow::anyhow!("无效的format类型")),
         }
     }
@@ -58,8 +166,10 @@ impl FromStr for TextSignFormat {
 impl From<TextSignFormat> for &'static str {
     fn from(value: TextSignFormat) -> Self {
         match value {
-            TextSignFormat::Blake3 => "black3",
+            TextSignFormat::Blake3 => "blake3",
             TextSignFormat::Ed25519 => "ed25519",
+            TextSignFormat::Ed25519ph => "ed25519ph",
+            TextSignFormat::SshSig => "sshsig",
         }
     }
 }
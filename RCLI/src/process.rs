@@ -3,9 +3,21 @@ mod gen_pass;
 mod b64;
 mod text;
 mod http_serve;
+mod jwt;
+mod hash;
+mod hmac;
+mod codec;
+mod hexdump;
+mod convert;
 
 pub use csv_convert::to_json_file;
 pub use gen_pass::process_gen_pass;
 pub use b64::process_base64;
 pub use text::process_text;
 pub use http_serve::process_http_serve;
+pub use jwt::process_jwt;
+pub use hash::process_hash;
+pub use hmac::process_hmac;
+pub use codec::{process_decode, process_encode};
+pub use hexdump::process_hexdump;
+pub use convert::process_convert;
@@ -1,49 +1,468 @@
-use crate::opts::base64::Base64Format;
+use crate::opts::base64::{Base64DecodeFormat, Base64Format};
+use crate::utils::get_writer;
 use crate::{Base64SubCommand, get_reader};
-use base64::Engine;
-use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
-use std::io::Read;
-use tracing::log;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::read::DecoderReader;
+use base64::write::EncoderWriter;
+use std::fmt::{Display, Formatter};
+use std::io::{BufWriter, Read, Write};
+
+/// 识别内容类型时最多保留的解码结果字节数
+const SNIFF_LEN: usize = 4096;
 
 pub fn process_base64(sub_command: &Base64SubCommand) -> anyhow::Result<()> {
     match sub_command {
-        Base64SubCommand::Encode(opts) => process_encode(&opts.input, opts.format)?,
-        Base64SubCommand::Decode(opts) => process_decode(&opts.input, opts.format)?,
+        Base64SubCommand::Encode(opts) => {
+            // 这里有个点需要注意。因为stdin和file返回的是两个不同的类型，所以不能同时返回给reader。所以这里需要找到这里俩的共同点：这俩都实现了Read trait。所以reader的类型可以是 Box<dyn Read>
+            let mut reader = get_reader(opts.input.trim())?;
+            let mut writer = BufWriter::new(get_writer(None)?);
+            let pad = padding(opts.pad, opts.no_pad);
+            let width = opts.wrap.unwrap_or_default();
+            process_encode(&mut reader, &mut writer, opts.format, pad, width)?;
+            writer.flush()?;
+        }
+        Base64SubCommand::Decode(opts) => {
+            let mut reader = get_reader(&opts.input)?;
+            // 解码结果可能是任意二进制内容，原样写出，不做UTF-8转换
+            let mut writer = BufWriter::new(get_writer(opts.output.as_deref())?);
+            let pad = padding(opts.pad, opts.no_pad);
+            let detection = process_decode(&mut reader, &mut writer, opts.format, pad)?;
+            writer.flush()?;
+            if let Some(detection) = detection {
+                eprintln!("{}", detection);
+            }
+        }
     }
     Ok(())
 }
 
-fn process_encode(input: &str, format: Base64Format) -> anyhow::Result<()> {
-    // 这里有个点需要注意。因为stdin和file返回的是两个不同的类型，所以不能同时返回给reader。所以这里需要找到这里俩的共同点：这俩都实现了Read trait。所以reader的类型可以是 Box<dyn Read>
-    let mut reader: Box<dyn Read> = get_reader(input.trim())?;
-    // 因为都实现了Read trait，所以都可以使用 read_to_end 读取数据
-    let mut buffer = Vec::new();
-    let _ = reader.read_to_end(&mut buffer);
+/// 边读边编码，内存占用与输入大小无关。width 为 0 时不换行
+fn process_encode(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64Format,
+    pad: Option<bool>,
+    width: usize,
+) -> anyhow::Result<()> {
+    // 未指定时保持原来的行为：standard 补齐，url-safe 不补齐
+    let pad = pad.unwrap_or(matches!(format, Base64Format::Standard));
+    let engine = engine(format, pad, DecodePaddingMode::Indifferent);
 
-    let encode = match format {
-        Base64Format::Standard => BASE64_STANDARD.encode(&buffer),
-        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.encode(&buffer),
-    };
-    log::info!("");
-    log::info!("{}", encode);
+    let mut wrapper = LineWrapper::new(writer, width);
+    let mut encoder = EncoderWriter::new(&mut wrapper, &engine);
+    std::io::copy(reader, &mut encoder)?;
+    // finish 会写出最后不足3字节的分组，并返回内层的 writer
+    encoder.finish()?.finish()?;
     Ok(())
 }
 
-fn process_decode(input: &str, format: Base64Format) -> anyhow::Result<()> {
-    // 这里有个点需要注意。因为stdin和file返回的是两个不同的类型，所以不能同时返回给reader。所以这里需要找到这里俩的共同点：这俩都实现了Read trait。所以reader的类型可以是 Box<dyn Read>
-    let mut reader = get_reader(input)?;
+/// 边读边解码，读取或解码出错时直接返回错误。auto 模式下返回识别结果
+fn process_decode(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    format: Base64DecodeFormat,
+    pad: Option<bool>,
+) -> anyhow::Result<Option<Detection>> {
+    let mode = match pad {
+        Some(true) => DecodePaddingMode::RequireCanonical,
+        Some(false) => DecodePaddingMode::RequireNone,
+        None => DecodePaddingMode::Indifferent,
+    };
+    // auto 模式下会把 url-safe 字符转换成 standard 字符，统一用 standard 解码
+    let (alphabet, auto) = match format {
+        Base64DecodeFormat::Standard => (Base64Format::Standard, false),
+        Base64DecodeFormat::UrlSafe => (Base64Format::UrlSafe, false),
+        Base64DecodeFormat::Auto => (Base64Format::Standard, true),
+    };
+    let engine = engine(alphabet, false, mode);
+    // 粘贴的证书、邮件附件通常带有换行，解码前去掉所有空白字符
+    let mut filter = InputFilter::new(reader, auto);
+    let mut sniffer = Sniffer::new(writer);
+    {
+        let mut decoder = DecoderReader::new(&mut filter, &engine);
+        std::io::copy(&mut decoder, &mut sniffer)?;
+    }
+    if !auto {
+        return Ok(None);
+    }
+    Ok(Some(Detection {
+        alphabet: filter.alphabet(),
+        padded: filter.padded,
+        payload: sniffer.payload(),
+    }))
+}
 
-    // 因为都实现了Read trait，所以都可以使用 read_to_end 读取数据
-    let mut buffer = String::new();
-    let _ = reader.read_to_string(&mut buffer);
-    let buffer = buffer.trim();
+/// --pad 与 --no-pad 都未指定时返回 None
+fn padding(pad: bool, no_pad: bool) -> Option<bool> {
+    match (pad, no_pad) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
 
-    let decode = match format {
-        Base64Format::Standard => BASE64_STANDARD.decode(buffer),
-        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.decode(buffer),
-    }?;
+pub(super) fn engine(
+    format: Base64Format,
+    encode_padding: bool,
+    decode_mode: DecodePaddingMode,
+) -> GeneralPurpose {
+    let alphabet = match format {
+        Base64Format::Standard => &alphabet::STANDARD,
+        Base64Format::UrlSafe => &alphabet::URL_SAFE,
+    };
+    let config = GeneralPurposeConfig::new()
+        .with_encode_padding(encode_padding)
+        .with_decode_padding_mode(decode_mode);
+    GeneralPurpose::new(alphabet, config)
+}
 
-    let decoded = String::from_utf8(decode)?;
-    log::info!("{}", decoded);
-    Ok(())
+/// 每写满 width 个字符插入一个换行
+struct LineWrapper<W> {
+    inner: W,
+    width: usize,
+    column: usize,
+}
+
+impl<W: Write> LineWrapper<W> {
+    fn new(inner: W, width: usize) -> Self {
+        Self {
+            inner,
+            width,
+            column: 0,
+        }
+    }
+
+    /// 输出最后的换行，与 println! 的行为保持一致
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.inner.write_all(b"\n")
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    // EncoderWriter 遇到只写入部分内容的 writer 时会返回 Ok(0)，所以这里每次都写完整个 buf
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.width == 0 {
+            self.inner.write_all(buf)?;
+            return Ok(buf.len());
+        }
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == self.width {
+                self.inner.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let n = rest.len().min(self.width - self.column);
+            self.inner.write_all(&rest[..n])?;
+            self.column += n;
+            rest = &rest[n..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 读取时跳过所有ASCII空白字符，并记录出现过的字母表与补齐
+struct InputFilter<R> {
+    inner: R,
+    normalize: bool,
+    standard: bool,
+    url_safe: bool,
+    padded: bool,
+}
+
+impl<R: Read> InputFilter<R> {
+    /// normalize 为 true 时把 url-safe 的 "-" "_" 转换成 "+" "/"
+    fn new(inner: R, normalize: bool) -> Self {
+        Self {
+            inner,
+            normalize,
+            standard: false,
+            url_safe: false,
+            padded: false,
+        }
+    }
+
+    fn alphabet(&self) -> &'static str {
+        match (self.standard, self.url_safe) {
+            (true, false) => "standard",
+            (false, true) => "url-safe",
+            (true, true) => "混合(standard 与 url-safe)",
+            // 没有出现区分两种字母表的字符，两种方式解码结果相同
+            (false, false) => "standard/url-safe",
+        }
+    }
+}
+
+impl<R: Read> Read for InputFilter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut len = 0;
+            for i in 0..n {
+                let mut c = buf[i];
+                match c {
+                    c if c.is_ascii_whitespace() => continue,
+                    b'+' | b'/' => self.standard = true,
+                    b'-' | b'_' => self.url_safe = true,
+                    b'=' => self.padded = true,
+                    _ => {}
+                }
+                if self.normalize {
+                    c = match c {
+                        b'-' => b'+',
+                        b'_' => b'/',
+                        c => c,
+                    };
+                }
+                buf[len] = c;
+                len += 1;
+            }
+            // 整块都是空白时继续读，避免返回 0 被当成读取结束
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+/// 写出解码结果的同时保留开头的一部分，用于识别内容类型
+struct Sniffer<W> {
+    inner: W,
+    head: Vec<u8>,
+    total: usize,
+}
+
+impl<W: Write> Sniffer<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            head: Vec::new(),
+            total: 0,
+        }
+    }
+
+    fn payload(&self) -> &'static str {
+        let head = self.head.as_slice();
+        let complete = self.total == head.len();
+        let magics: [(&[u8], &'static str); 6] = [
+            (b"\x1f\x8b", "gzip 压缩数据"),
+            (b"\x89PNG\r\n\x1a\n", "PNG 图片"),
+            (b"\xff\xd8\xff", "JPEG 图片"),
+            (b"GIF8", "GIF 图片"),
+            (b"%PDF-", "PDF 文档"),
+            (b"PK\x03\x04", "zip 压缩包"),
+        ];
+        if head.is_empty() {
+            return "空内容";
+        }
+        if let Some((_, name)) = magics.iter().find(|(magic, _)| head.starts_with(magic)) {
+            return name;
+        }
+        let text = match std::str::from_utf8(head) {
+            Ok(text) => text,
+            // 开头的字节被截断在一个多字节字符中间时，也认为是文本
+            Err(e) if !complete && e.error_len().is_none() => {
+                std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(_) => return "二进制数据",
+        };
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') || trimmed.starts_with('[') {
+            // 内容不完整时无法完整解析，只根据开头判断
+            if !complete || serde_json::from_str::<serde_json::Value>(text).is_ok() {
+                return "JSON";
+            }
+        }
+        "UTF-8 文本"
+    }
+}
+
+impl<W: Write> Write for Sniffer<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        let keep = n.min(SNIFF_LEN - self.head.len());
+        self.head.extend_from_slice(&buf[..keep]);
+        self.total += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// auto 模式的识别结果
+#[derive(Debug)]
+struct Detection {
+    alphabet: &'static str,
+    padded: bool,
+    payload: &'static str,
+}
+
+impl Display for Detection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "识别结果: 字母表 {}，{}，内容类型 {}",
+            self.alphabet,
+            if self.padded {
+                "有补齐"
+            } else {
+                "无补齐"
+            },
+            self.payload
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+
+    fn encode(data: &[u8], format: Base64Format, pad: Option<bool>, width: usize) -> String {
+        let mut output = Vec::new();
+        process_encode(&mut &data[..], &mut output, format, pad, width).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn decode(
+        data: &str,
+        format: Base64DecodeFormat,
+        pad: Option<bool>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        process_decode(&mut data.as_bytes(), &mut output, format, pad)?;
+        Ok(output)
+    }
+
+    fn detect(data: &[u8], format: Base64Format, pad: Option<bool>) -> Detection {
+        let encoded = encode(data, format, pad, 0);
+        let mut output = Vec::new();
+        let detection = process_decode(
+            &mut encoded.as_bytes(),
+            &mut output,
+            Base64DecodeFormat::Auto,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(output, data);
+        detection
+    }
+
+    #[test]
+    fn test_decode_binary() -> anyhow::Result<()> {
+        // 非UTF-8的二进制内容也能原样解码
+        let data: Vec<u8> = (0..=255).collect();
+        let cases = [
+            (Base64DecodeFormat::Standard, BASE64_STANDARD.encode(&data)),
+            (
+                Base64DecodeFormat::UrlSafe,
+                BASE64_URL_SAFE_NO_PAD.encode(&data),
+            ),
+        ];
+        for (format, encoded) in cases {
+            assert_eq!(decode(&format!("{}\n", encoded), format, None)?, data);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_lenient() -> anyhow::Result<()> {
+        let data = b"hello, rcli!!";
+        let encoded = BASE64_STANDARD.encode(data);
+
+        // 带换行、带补齐
+        let wrapped = encode(data, Base64Format::Standard, None, 4).replace('\n', "\r\n");
+        assert_eq!(decode(&wrapped, Base64DecodeFormat::Standard, None)?, data);
+        assert_eq!(
+            decode(&wrapped, Base64DecodeFormat::Standard, Some(true))?,
+            data
+        );
+        assert!(decode(&wrapped, Base64DecodeFormat::Standard, Some(false)).is_err());
+
+        // 不带补齐
+        let unpadded = encoded.trim_end_matches('=');
+        assert_eq!(decode(unpadded, Base64DecodeFormat::Standard, None)?, data);
+        assert!(decode(unpadded, Base64DecodeFormat::Standard, Some(true)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode() -> anyhow::Result<()> {
+        let data = std::fs::read("assets/people-100.csv")?;
+        let encoded = encode(&data, Base64Format::Standard, None, 0);
+        assert_eq!(encoded, format!("{}\n", BASE64_STANDARD.encode(&data)));
+
+        let encoded = encode(&data, Base64Format::UrlSafe, Some(true), 76);
+        let lines: Vec<&str> = encoded.lines().collect();
+        assert!(lines.iter().all(|line| line.len() <= 76));
+        assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == 76));
+        assert!(lines.concat().len() % 4 == 0);
+        assert_eq!(decode(&encoded, Base64DecodeFormat::UrlSafe, None)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_error_propagates() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("磁盘读取失败"))
+            }
+        }
+
+        let mut output = Vec::new();
+        assert!(
+            process_encode(
+                &mut FailingReader,
+                &mut output,
+                Base64Format::Standard,
+                None,
+                0
+            )
+            .is_err()
+        );
+        assert!(
+            process_decode(
+                &mut FailingReader,
+                &mut output,
+                Base64DecodeFormat::Standard,
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decode_auto() {
+        let binary: Vec<u8> = (0..=255).collect();
+        let detection = detect(&binary, Base64Format::UrlSafe, None);
+        assert_eq!(detection.alphabet, "url-safe");
+        assert!(!detection.padded);
+        assert_eq!(detection.payload, "二进制数据");
+
+        let detection = detect(&binary, Base64Format::Standard, None);
+        assert_eq!(detection.alphabet, "standard");
+        assert!(detection.padded);
+
+        let json = br#"{"name": "rcli", "tags": ["base64"]}"#;
+        assert_eq!(detect(json, Base64Format::Standard, None).payload, "JSON");
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        assert_eq!(detect(png, Base64Format::UrlSafe, None).payload, "PNG 图片");
+        let text = "你好, rcli".as_bytes();
+        assert_eq!(
+            detect(text, Base64Format::Standard, None).payload,
+            "UTF-8 文本"
+        );
+    }
 }
@@ -0,0 +1,296 @@
+use super::b64::engine;
+use crate::get_reader;
+use crate::opts::base64::Base64Format;
+use crate::opts::codec::{Codec, DecodeOpts, EncodeOpts};
+use crate::utils::get_writer;
+use base64::Engine;
+use base64::engine::DecodePaddingMode;
+use data_encoding::{BASE32_NOPAD, BASE32HEX_NOPAD};
+use std::io::{BufWriter, Read, Write};
+
+/// 流式处理时每次读取的分组数
+const GROUPS_PER_CHUNK: usize = 16 * 1024;
+
+pub fn process_encode(opts: &EncodeOpts) -> anyhow::Result<()> {
+    let mut reader = get_reader(&opts.input)?;
+    let mut writer = BufWriter::new(get_writer(None)?);
+    encode_stream(&mut reader, &mut writer, opts.codec)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn process_decode(opts: &DecodeOpts) -> anyhow::Result<()> {
+    let mut reader = get_reader(&opts.input)?;
+    let mut writer = BufWriter::new(get_writer(opts.output.as_deref())?);
+    decode_stream(&mut reader, &mut writer, opts.codec)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// 可以分块处理的编码，返回一个完整分组的 (原始字节数, 编码后字符数)。
+/// base58 是整体的大数转换，ascii85 的 "z" 会打乱分组边界，这两种只能一次性读入内存处理
+fn group_size(codec: Codec) -> Option<(usize, usize)> {
+    match codec {
+        Codec::Base64 | Codec::Base64Url => Some((3, 4)),
+        Codec::Base32 | Codec::Base32Hex => Some((5, 8)),
+        Codec::Hex => Some((1, 2)),
+        Codec::Base58 | Codec::Ascii85 => None,
+    }
+}
+
+/// 按完整分组边读边编码，只有最后一块会带上补齐
+fn encode_stream(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    codec: Codec,
+) -> anyhow::Result<()> {
+    let Some((raw, _)) = group_size(codec) else {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        writeln!(writer, "{}", encode(&buffer, codec))?;
+        return Ok(());
+    };
+    let chunk_len = raw * GROUPS_PER_CHUNK;
+    let mut chunk = Vec::with_capacity(chunk_len);
+    loop {
+        chunk.clear();
+        // take 保证除了最后一块，每块都是完整的分组
+        reader.take(chunk_len as u64).read_to_end(&mut chunk)?;
+        writer.write_all(encode(&chunk, codec).as_bytes())?;
+        if chunk.len() < chunk_len {
+            break;
+        }
+    }
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// 去掉空白后按完整分组边读边解码。最后一个分组留到读完再处理，补齐字符只能出现在其中
+fn decode_stream(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    codec: Codec,
+) -> anyhow::Result<()> {
+    let Some((_, group)) = group_size(codec) else {
+        let mut buffer = String::new();
+        reader.read_to_string(&mut buffer)?;
+        writer.write_all(&decode(&buffer, codec)?)?;
+        return Ok(());
+    };
+    let chunk_len = group * GROUPS_PER_CHUNK;
+    let mut chunk = Vec::with_capacity(chunk_len);
+    let mut pending = Vec::new();
+    let mut first = true;
+    loop {
+        chunk.clear();
+        reader.take(chunk_len as u64).read_to_end(&mut chunk)?;
+        let eof = chunk.is_empty();
+        pending.extend(chunk.iter().filter(|b| !b.is_ascii_whitespace()));
+        if first && codec == Codec::Hex && (pending.len() >= 2 || eof) {
+            if pending.starts_with(b"0x") {
+                pending.drain(..2);
+            }
+            first = false;
+        }
+
+        let len = if eof {
+            pending.len()
+        } else {
+            (pending.len().saturating_sub(1) / group) * group
+        };
+        let data = std::str::from_utf8(&pending[..len])
+            .map_err(|_| anyhow::anyhow!("无效的{}内容", codec))?;
+        if !eof && data.contains('=') {
+            anyhow::bail!("补齐字符只能出现在{}内容的末尾", codec);
+        }
+        writer.write_all(&decode(data, codec)?)?;
+        pending.drain(..len);
+        if eof {
+            return Ok(());
+        }
+    }
+}
+
+fn encode(data: &[u8], codec: Codec) -> String {
+    match codec {
+        Codec::Base64 => {
+            engine(Base64Format::Standard, true, DecodePaddingMode::Indifferent).encode(data)
+        }
+        Codec::Base64Url => {
+            engine(Base64Format::UrlSafe, false, DecodePaddingMode::Indifferent).encode(data)
+        }
+        Codec::Base32 => data_encoding::BASE32.encode(data),
+        Codec::Base32Hex => data_encoding::BASE32HEX.encode(data),
+        Codec::Base58 => bs58::encode(data).into_string(),
+        Codec::Hex => hex::encode(data),
+        Codec::Ascii85 => ascii85_encode(data),
+    }
+}
+
+fn decode(data: &str, codec: Codec) -> anyhow::Result<Vec<u8>> {
+    // 忽略换行等空白字符，便于直接粘贴
+    let mut data = data.to_string();
+    data.retain(|c| !c.is_ascii_whitespace());
+
+    let decoded = match codec {
+        Codec::Base64 => {
+            engine(Base64Format::Standard, true, DecodePaddingMode::Indifferent).decode(&data)?
+        }
+        Codec::Base64Url => {
+            engine(Base64Format::UrlSafe, false, DecodePaddingMode::Indifferent).decode(&data)?
+        }
+        // TOTP 密钥通常是小写且不带补齐的，这里统一处理
+        Codec::Base32 => {
+            BASE32_NOPAD.decode(data.trim_end_matches('=').to_uppercase().as_bytes())?
+        }
+        Codec::Base32Hex => {
+            BASE32HEX_NOPAD.decode(data.trim_end_matches('=').to_uppercase().as_bytes())?
+        }
+        Codec::Base58 => bs58::decode(&data).into_vec()?,
+        Codec::Hex => {
+            let data = data.strip_prefix("0x").unwrap_or(&data);
+            hex::decode(data)?
+        }
+        Codec::Ascii85 => ascii85_decode(&data)?,
+    };
+    Ok(decoded)
+}
+
+/// Ascii85 编码，全零的4字节分组写成 "z"，不输出 <~ ~> 定界符
+fn ascii85_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() * 5 / 4 + 5);
+    for chunk in data.chunks(4) {
+        if chunk == [0, 0, 0, 0] {
+            encoded.push('z');
+            continue;
+        }
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % 85) as u8 + b'!';
+            value /= 85;
+        }
+        // 不足4字节的分组只输出 n+1 个字符
+        encoded.extend(digits[..chunk.len() + 1].iter().map(|&c| c as char));
+    }
+    encoded
+}
+
+fn ascii85_decode(data: &str) -> anyhow::Result<Vec<u8>> {
+    let data = data.strip_prefix("<~").unwrap_or(data);
+    let data = data.strip_suffix("~>").unwrap_or(data);
+
+    let mut decoded = Vec::with_capacity(data.len() * 4 / 5);
+    let mut group = Vec::with_capacity(5);
+    for c in data.bytes() {
+        match c {
+            b'z' if group.is_empty() => decoded.extend_from_slice(&[0, 0, 0, 0]),
+            b'!'..=b'u' => {
+                group.push(c - b'!');
+                if group.len() == 5 {
+                    decoded.extend_from_slice(&ascii85_group(&group)?);
+                    group.clear();
+                }
+            }
+            _ => anyhow::bail!("无效的ascii85字符: {}", c as char),
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => anyhow::bail!("ascii85内容长度不正确"),
+        n => {
+            // 不足5个字符的分组用 "u" 补齐，解码后只保留 n-1 个字节
+            group.resize(5, b'u' - b'!');
+            decoded.extend_from_slice(&ascii85_group(&group)?[..n - 1]);
+        }
+    }
+    Ok(decoded)
+}
+
+fn ascii85_group(group: &[u8]) -> anyhow::Result<[u8; 4]> {
+    let value = group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+    let value = u32::try_from(value).map_err(|_| anyhow::anyhow!("ascii85分组超出范围"))?;
+    Ok(value.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_codec_known_values() -> anyhow::Result<()> {
+        let data = b"hello world";
+        let cases = [
+            (Codec::Base64, "aGVsbG8gd29ybGQ="),
+            (Codec::Base64Url, "aGVsbG8gd29ybGQ"),
+            (Codec::Base32, "NBSWY3DPEB3W64TMMQ======"),
+            (Codec::Base32Hex, "D1IMOR3F41RMUSJCCG======"),
+            (Codec::Base58, "StV1DL6CwTryKyV"),
+            (Codec::Hex, "68656c6c6f20776f726c64"),
+            (Codec::Ascii85, "BOu!rD]j7BEbo7"),
+        ];
+        for (codec, expected) in cases {
+            assert_eq!(encode(data, codec), expected, "{}", codec);
+            assert_eq!(decode(expected, codec)?, data, "{}", codec);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_roundtrip() -> anyhow::Result<()> {
+        let data: Vec<u8> = (0..=255).chain([0, 0, 0, 0, 0]).collect();
+        for codec in Codec::value_variants() {
+            assert_eq!(decode(&encode(&data, *codec), *codec)?, data, "{}", codec);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_stream() -> anyhow::Result<()> {
+        // 超过一个分块，覆盖分块边界
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        for codec in Codec::value_variants() {
+            if group_size(*codec).is_none() {
+                continue;
+            }
+            let mut encoded = Vec::new();
+            encode_stream(&mut data.as_slice(), &mut encoded, *codec)?;
+            let encoded = String::from_utf8(encoded)?;
+            assert_eq!(encoded, format!("{}\n", encode(&data, *codec)), "{}", codec);
+
+            // 插入换行，分组跨越读取边界
+            let wrapped: String = encoded
+                .chars()
+                .enumerate()
+                .flat_map(|(i, c)| (i % 76 == 75).then_some('\n').into_iter().chain([c]))
+                .collect();
+            let mut decoded = Vec::new();
+            decode_stream(&mut wrapped.as_bytes(), &mut decoded, *codec)?;
+            assert_eq!(decoded, data, "{}", codec);
+        }
+
+        let mut decoded = Vec::new();
+        decode_stream(&mut &b"0x6869"[..], &mut decoded, Codec::Hex)?;
+        assert_eq!(decoded, b"hi");
+        // 补齐字符后面还有内容
+        let padded = format!("aGk={}", "aGk=".repeat(GROUPS_PER_CHUNK));
+        let result = decode_stream(&mut padded.as_bytes(), &mut Vec::new(), Codec::Base64);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_lenient() -> anyhow::Result<()> {
+        // 小写、不带补齐的TOTP密钥
+        assert_eq!(decode("nbswy3dp\n", Codec::Base32)?, b"hello");
+        assert_eq!(decode("0x6869", Codec::Hex)?, b"hi");
+        assert_eq!(decode("<~BOu!rDZ~>", Codec::Ascii85)?, b"hello");
+        assert_eq!(decode("z", Codec::Ascii85)?, [0, 0, 0, 0]);
+        assert!(decode("BOu!rD~", Codec::Ascii85).is_err());
+        assert!(decode("0OIl", Codec::Base58).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use super::csv_convert::{CsvWriteOptions, read_csv, write_records};
+use crate::get_reader;
+use crate::opts::convert::ConvertOpts;
+use crate::opts::csv::InputFormat;
+use crate::utils::get_writer;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+pub fn process_convert(opts: &ConvertOpts) -> anyhow::Result<()> {
+    let reader = get_reader(&opts.input)?;
+    let records = read_input(reader, opts.from, opts.delimiter)?;
+
+    let csv_options = CsvWriteOptions {
+        delimiter: opts.delimiter,
+        arrays: opts.arrays,
+        separator: opts.separator.clone(),
+        columns: opts.columns.clone(),
+    };
+    let mut writer = BufWriter::new(get_writer(opts.output.as_deref())?);
+    write_records(&mut writer, records, opts.to, &csv_options)?;
+    writer.flush()?;
+    Ok(())
+}
+
+type Records<'a> = Box<dyn Iterator<Item = anyhow::Result<Value>> + 'a>;
+
+/// CSV 和 NDJSON 逐条读取记录。JSON/YAML 需要整体解析，顶层可以是数组或单个对象
+fn read_input<'a, R: Read + 'a>(
+    reader: R,
+    format: InputFormat,
+    delimiter: u8,
+) -> anyhow::Result<Records<'a>> {
+    let records: Records =
+        match format {
+            InputFormat::Csv => Box::new(read_csv(reader, delimiter)?),
+            InputFormat::Json => into_records(serde_json::from_reader(reader)?),
+            InputFormat::Yaml => into_records(serde_yaml::from_reader(reader)?),
+            InputFormat::Ndjson => Box::new(BufReader::new(reader).lines().enumerate().filter_map(
+                |(lineno, line)| match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => {
+                        Some(serde_json::from_str(&line).map_err(|e| {
+                            anyhow::anyhow!("第{}行不是有效的JSON: {}", lineno + 1, e)
+                        }))
+                    }
+                    Err(e) => Some(Err(e.into())),
+                },
+            )),
+        };
+    Ok(records)
+}
+
+fn into_records<'a>(value: Value) -> Records<'a> {
+    match value {
+        Value::Array(records) => Box::new(records.into_iter().map(Ok)),
+        value => Box::new(std::iter::once(Ok(value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn read_all(data: &str, format: InputFormat, delimiter: u8) -> anyhow::Result<Vec<Value>> {
+        read_input(data.as_bytes(), format, delimiter)?.collect()
+    }
+
+    #[test]
+    fn test_read_input() -> anyhow::Result<()> {
+        let ndjson = "{\"a\": 1}\n\n{\"b\": {\"c\": true}}\n";
+        let records = read_all(ndjson, InputFormat::Ndjson, b',')?;
+        assert_eq!(records[1], json!({"b": {"c": true}}));
+
+        let yaml = "- a: 1\n  b: [x, y]\n";
+        let records = read_all(yaml, InputFormat::Yaml, b',')?;
+        assert_eq!(records, vec![json!({"a": 1, "b": ["x", "y"]})]);
+
+        let records = read_all("{\"a\": 1}", InputFormat::Json, b',')?;
+        assert_eq!(records, vec![json!({"a": 1})]);
+
+        let records = read_all("a;b\n1;x\n", InputFormat::Csv, b';')?;
+        assert_eq!(records, vec![json!({"a": 1, "b": "x"})]);
+
+        assert!(read_all("{\"a\": 1}\n{", InputFormat::Ndjson, b',').is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_ndjson_lazily() -> anyhow::Result<()> {
+        // 逐条读取，出错的行之前的记录可以先写出
+        let mut records = read_input("{\"a\": 1}\n{".as_bytes(), InputFormat::Ndjson, b',')?;
+        assert_eq!(records.next().transpose()?, Some(json!({"a": 1})));
+        assert!(records.next().unwrap().is_err());
+        Ok(())
+    }
+}
@@ -1,23 +1,435 @@
-use serde_json::Value;
-use std::fs;
-use crate::opts::csv::OutputFormat;
+mod flatten;
+mod infer;
+
+use crate::opts::csv::{CsvOpts, OutputFormat};
+pub(crate) use flatten::CsvWriteOptions;
+use indicatif::{ProgressBar, ProgressStyle};
+use infer::TypeInference;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
 
 // 将数据写成json文件
-pub fn to_json_file(input: &str, output: String, format: OutputFormat) -> anyhow::Result<()> {
-    let mut reader = csv::Reader::from_path(input)?;
-    let headers = reader.headers()?.clone();
-    let mut ret = Vec::with_capacity(200);
-    for result in reader.records() {
-        let record = result?;
-        let json_value = headers.iter().zip(record.iter()).collect::<Value>();
-        ret.push(json_value);
-    }
-
-    let content = match format {
-        OutputFormat::Json => serde_json::to_string_pretty(&ret)?,
-        OutputFormat::Yaml => serde_yaml::to_string(&ret)?,
+pub fn to_json_file(opts: &CsvOpts, output: &str) -> anyhow::Result<()> {
+    let file = File::open(&opts.input)?;
+    // 按读取的字节数显示进度，stderr 不是终端时不会显示
+    let progress = ProgressBar::new(file.metadata()?.len()).with_style(
+        ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} {bytes_per_sec} 剩余 {eta}")?,
+    );
+    let reader = csv::ReaderBuilder::new()
+        .delimiter(opts.delimiter)
+        .has_headers(opts.header)
+        .from_reader(progress.wrap_read(file));
+    let inference = TypeInference::new(!opts.no_infer, opts.infer_dates, opts.schema.as_deref())?;
+    let records = CsvRecords::new(reader, opts.header, opts.columns.as_deref(), inference)?;
+
+    let csv_options = CsvWriteOptions {
+        delimiter: opts.delimiter,
+        ..Default::default()
     };
+    let mut writer = BufWriter::new(File::create(output)?);
+    write_records(&mut writer, records, opts.format, &csv_options)?;
+    writer.flush()?;
+    progress.finish_and_clear();
+    Ok(())
+}
+
+/// 逐条读取有头的CSV，并推断各列的类型
+pub(super) fn read_csv<R: Read>(reader: R, delimiter: u8) -> anyhow::Result<CsvRecords<R>> {
+    let reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(reader);
+    let inference = TypeInference::new(true, false, None)?;
+    CsvRecords::new(reader, true, None, inference)
+}
+
+/// 逐条读取CSV记录，并转换成列名到值的对象
+pub(super) struct CsvRecords<R> {
+    reader: csv::Reader<R>,
+    headers: Option<Vec<String>>,
+    inference: TypeInference,
+    record: csv::StringRecord,
+}
+
+impl<R: Read> CsvRecords<R> {
+    fn new(
+        mut reader: csv::Reader<R>,
+        header: bool,
+        columns: Option<&[String]>,
+        inference: TypeInference,
+    ) -> anyhow::Result<Self> {
+        let headers: Option<Vec<String>> = match columns {
+            Some(columns) => Some(columns.to_vec()),
+            None if header => Some(reader.headers()?.iter().map(String::from).collect()),
+            // 没有头时，根据第一行的列数生成 col1..colN
+            None => None,
+        };
+        if let (Some(columns), true) = (columns, header) {
+            let len = reader.headers()?.len();
+            if columns.len() != len {
+                anyhow::bail!("--columns 指定了{}列，CSV文件有{}列", columns.len(), len);
+            }
+        }
+        Ok(Self {
+            reader,
+            headers,
+            inference,
+            record: csv::StringRecord::new(),
+        })
+    }
 
-    fs::write(output, content)?;
+    fn read_next(&mut self) -> anyhow::Result<Option<Value>> {
+        if !self.reader.read_record(&mut self.record)? {
+            return Ok(None);
+        }
+        let record = &self.record;
+        let line = record.position().map_or(0, |p| p.line());
+        let headers = self
+            .headers
+            .get_or_insert_with(|| (1..=record.len()).map(|i| format!("col{}", i)).collect());
+        if headers.len() != record.len() {
+            anyhow::bail!(
+                "第{}行有{}列，列名有{}个",
+                line,
+                record.len(),
+                headers.len()
+            );
+        }
+        let json_value = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(name, value)| Ok((name.clone(), self.inference.convert(name, value)?)))
+            .collect::<anyhow::Result<serde_json::Map<_, _>>>()
+            .map_err(|e| anyhow::anyhow!("第{}行: {}", line, e))?;
+        Ok(Some(Value::Object(json_value)))
+    }
+}
+
+impl<R: Read> Iterator for CsvRecords<R> {
+    type Item = anyhow::Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// 逐条写出记录，内存占用与记录数无关。
+/// CSV 没有指定列时需要先知道所有的列、Markdown 需要先知道每列的宽度，这两种情况会先读取全部记录
+pub(super) fn write_records<W: Write>(
+    writer: &mut W,
+    records: impl Iterator<Item = anyhow::Result<Value>>,
+    format: OutputFormat,
+    csv_options: &CsvWriteOptions,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Csv => match &csv_options.columns {
+            Some(columns) => flatten::write_csv(&mut *writer, records, columns, csv_options)?,
+            None => {
+                let records = records.collect::<anyhow::Result<Vec<_>>>()?;
+                writer.write_all(flatten::to_csv(&records, csv_options)?.as_bytes())?;
+            }
+        },
+        OutputFormat::Markdown => {
+            let records = records.collect::<anyhow::Result<Vec<_>>>()?;
+            writer.write_all(to_markdown(&records).as_bytes())?;
+        }
+        OutputFormat::Json => {
+            // 手动输出数组的括号和逗号，格式与 to_string_pretty 整个数组时一致
+            let mut empty = true;
+            writer.write_all(b"[")?;
+            for record in records {
+                let record = serde_json::to_string_pretty(&record?)?;
+                writer.write_all(if empty { b"\n" } else { b",\n" })?;
+                for (i, line) in record.lines().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b"\n")?;
+                    }
+                    write!(writer, "  {}", line)?;
+                }
+                empty = false;
+            }
+            writer.write_all(if empty { b"]" } else { b"\n]" })?;
+        }
+        OutputFormat::Yaml => {
+            // 每条记录输出为只有一个元素的序列，拼起来就是完整的序列
+            let mut empty = true;
+            for record in records {
+                writer.write_all(serde_yaml::to_string(&[record?])?.as_bytes())?;
+                empty = false;
+            }
+            if empty {
+                writer.write_all(b"[]\n")?;
+            }
+        }
+        // 每行一个JSON对象
+        OutputFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut *writer, &record?)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        // TOML 的顶层必须是表，所以把所有记录放到 records 这个表数组里
+        // 每条记录输出一个 [[records]] 块，TOML数组表的元素只能是表
+        OutputFormat::Toml => {
+            for (i, record) in records.enumerate() {
+                let record = record?;
+                if !record.is_object() {
+                    anyhow::bail!("TOML格式只支持对象记录，第{}条记录是: {}", i + 1, record);
+                }
+                if i > 0 {
+                    writer.write_all(b"\n")?;
+                }
+                let table = json!({ "records": [without_null(record)] });
+                writer.write_all(toml::to_string(&table)?.as_bytes())?;
+            }
+        }
+        // 列名不一定是合法的XML标签名，所以放到 name 属性里
+        OutputFormat::Xml => {
+            writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records>\n")?;
+            for record in records {
+                writer.write_all(b"  <record>\n")?;
+                for (name, value) in record?.as_object().into_iter().flatten() {
+                    writeln!(
+                        writer,
+                        "    <field name=\"{}\">{}</field>",
+                        xml_escape(name),
+                        xml_escape(&cell(value))
+                    )?;
+                }
+                writer.write_all(b"  </record>\n")?;
+            }
+            writer.write_all(b"</records>\n")?;
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// TOML 没有 null，递归去掉对象中值为空的字段和数组中的空元素
+fn without_null(value: Value) -> Value {
+    match value {
+        Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(name, value)| (name, without_null(value)))
+            .collect(),
+        Value::Array(items) => items
+            .into_iter()
+            .filter(|item| !item.is_null())
+            .map(without_null)
+            .collect(),
+        value => value,
+    }
+}
+
+/// 所有记录中出现过的列，按第一次出现的顺序排列
+fn columns(records: &[Value]) -> Vec<String> {
+    let mut columns = Vec::new();
+    let mut seen = HashSet::new();
+    for name in records
+        .iter()
+        .filter_map(Value::as_object)
+        .flat_map(|map| map.keys())
+    {
+        if seen.insert(name) {
+            columns.push(name.clone());
+        }
+    }
+    columns
+}
+
+fn to_markdown(records: &[Value]) -> String {
+    let headers = columns(records);
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| {
+            headers
+                .iter()
+                .map(|name| record.get(name).map(cell).unwrap_or_default())
+                .map(|value| markdown_escape(&value))
+                .collect()
+        })
+        .collect();
+    let headers: Vec<String> = headers.iter().map(|name| markdown_escape(name)).collect();
+
+    // 按每列最长的内容对齐，方便直接在终端里查看
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([headers[i].chars().count(), 3])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell))
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut content = line(&headers);
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    content.push_str(&line(&separator));
+    for row in &rows {
+        content.push_str(&line(row));
+    }
+    content
+}
+
+/// 单元格的文本内容，字符串不带引号
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn markdown_escape(s: &str) -> String {
+    s.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Value> {
+        vec![
+            json!({"Name": "Tom & Jerry", "Note": "a|b"}),
+            json!({"Name": "<rcli>", "Note": ""}),
+        ]
+    }
+
+    fn convert(records: &[Value], format: OutputFormat) -> String {
+        let mut output = Vec::new();
+        let records = records.iter().cloned().map(Ok);
+        write_records(&mut output, records, format, &CsvWriteOptions::default()).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_convert_text_formats() {
+        let records = records();
+
+        let ndjson = convert(&records, OutputFormat::Ndjson);
+        assert_eq!(
+            ndjson,
+            "{\"Name\":\"Tom & Jerry\",\"Note\":\"a|b\"}\n{\"Name\":\"<rcli>\",\"Note\":\"\"}\n"
+        );
+
+        let xml = convert(&records, OutputFormat::Xml);
+        assert!(xml.contains("<field name=\"Name\">Tom &amp; Jerry</field>"));
+        assert!(xml.contains("<field name=\"Name\">&lt;rcli&gt;</field>"));
+
+        let markdown = convert(&records, OutputFormat::Markdown);
+        let expected = "\
+| Name        | Note |
+| ----------- | ---- |
+| Tom & Jerry | a\\|b |
+| <rcli>      |      |
+";
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_convert_streaming_matches_whole() -> anyhow::Result<()> {
+        // 逐条输出的结果与一次性序列化整个数组的结果一致
+        let records = records();
+        assert_eq!(
+            convert(&records, OutputFormat::Json),
+            serde_json::to_string_pretty(&records)?
+        );
+        assert_eq!(
+            convert(&records, OutputFormat::Yaml),
+            serde_yaml::to_string(&records)?
+        );
+        assert_eq!(
+            convert(&records, OutputFormat::Toml),
+            toml::to_string(&json!({ "records": records }))?
+        );
+        assert_eq!(convert(&[], OutputFormat::Json), "[]");
+        assert_eq!(convert(&[], OutputFormat::Yaml), "[]\n");
+        Ok(())
+    }
+
+    fn read(data: &str, delimiter: u8, header: bool, columns: Option<&[String]>) -> Vec<Value> {
+        let reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(header)
+            .from_reader(data.as_bytes());
+        CsvRecords::new(reader, header, columns, TypeInference::default())
+            .unwrap()
+            .collect::<anyhow::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_records() {
+        let records = read("a;b\n1;2\n", b';', true, None);
+        assert_eq!(records, vec![json!({"a": "1", "b": "2"})]);
+
+        let records = read("1\t2\n3\t4\n", b'\t', false, None);
+        assert_eq!(records[1], json!({"col1": "3", "col2": "4"}));
+
+        let columns = ["x".to_string(), "y".to_string()];
+        let records = read("1,2\n", b',', false, Some(&columns));
+        assert_eq!(records, vec![json!({"x": "1", "y": "2"})]);
+        let records = read("a,b\n1,2\n", b',', true, Some(&columns));
+        assert_eq!(records, vec![json!({"x": "1", "y": "2"})]);
+
+        let reader = csv::ReaderBuilder::new().from_reader("a,b\n1,2\n".as_bytes());
+        let inference = TypeInference::default();
+        assert!(CsvRecords::new(reader, true, Some(&columns[..1]), inference).is_err());
+    }
+
+    #[test]
+    fn test_read_records_infer() -> anyhow::Result<()> {
+        let records = read_csv(File::open("assets/people-100.csv")?, b',')?
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(records.len(), 100);
+        assert_eq!(records[0]["Index"], json!(1));
+        assert_eq!(records[0]["Phone"], json!("001-084-906-7849x73518"));
+
+        let toml = convert(&[json!({"a": 1, "b": null})], OutputFormat::Toml);
+        assert_eq!(toml, "[[records]]\na = 1\n");
+
+        let record = json!({"a": {"b": null, "c": 1}, "d": [1, null, {"e": null}]});
+        let toml = convert(&[record], OutputFormat::Toml);
+        let parsed: toml::Table = toml::from_str(&toml)?;
+        let expected: toml::Table = toml::from_str("[[records]]\na = { c = 1 }\nd = [1, {}]\n")?;
+        assert_eq!(parsed, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_toml() -> anyhow::Result<()> {
+        let toml = convert(&records(), OutputFormat::Toml);
+        let parsed: toml::Table = toml::from_str(&toml)?;
+        let rows = parsed["records"].as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["Name"].as_str(), Some("Tom & Jerry"));
+
+        let mut output = Vec::new();
+        let records = [json!({"a": 1}), json!([1, 2])].into_iter().map(Ok);
+        let result = write_records(
+            &mut output,
+            records,
+            OutputFormat::Toml,
+            &CsvWriteOptions::default(),
+        );
+        assert!(result.is_err());
+        Ok(())
+    }
+}
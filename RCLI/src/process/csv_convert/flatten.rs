@@ -0,0 +1,239 @@
+use super::cell;
+use crate::opts::convert::ArrayMode;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+/// 输出CSV时的选项
+pub(crate) struct CsvWriteOptions {
+    pub(crate) delimiter: u8,
+    pub(crate) arrays: ArrayMode,
+    /// join 方式下数组元素之间的分隔符
+    pub(crate) separator: String,
+    /// 指定输出的列时可以逐条写出，不需要先读取全部记录
+    pub(crate) columns: Option<Vec<String>>,
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            arrays: ArrayMode::Join,
+            separator: ";".to_string(),
+            columns: None,
+        }
+    }
+}
+
+/// 把记录展开成CSV。嵌套对象用 "." 连接成列名，列为所有记录中出现过的键的并集
+pub(super) fn to_csv(records: &[Value], options: &CsvWriteOptions) -> anyhow::Result<String> {
+    let rows: Vec<Vec<(String, Value)>> = records
+        .iter()
+        .flat_map(|record| to_rows(record, options))
+        .collect();
+
+    // 按第一次出现的顺序排列各列
+    let mut columns: Vec<&str> = Vec::new();
+    let mut seen = HashSet::new();
+    for (name, _) in rows.iter().flatten() {
+        if seen.insert(name.as_str()) {
+            columns.push(name);
+        }
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for row in &rows {
+        write_row(&mut writer, &columns, row)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// 按指定的列逐条展开并写出，不在列中的字段会被忽略
+pub(super) fn write_csv<W: Write>(
+    writer: W,
+    records: impl Iterator<Item = anyhow::Result<Value>>,
+    columns: &[String],
+    options: &CsvWriteOptions,
+) -> anyhow::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    writer.write_record(columns)?;
+    for record in records {
+        for row in to_rows(&record?, options) {
+            write_row(&mut writer, columns, &row)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_row<W: Write, S: AsRef<str>>(
+    writer: &mut csv::Writer<W>,
+    columns: &[S],
+    row: &[(String, Value)],
+) -> csv::Result<()> {
+    let row: HashMap<&str, &Value> = row
+        .iter()
+        .map(|(name, value)| (name.as_str(), value))
+        .collect();
+    writer.write_record(columns.iter().map(|name| {
+        row.get(name.as_ref())
+            .map(|value| cell(value))
+            .unwrap_or_default()
+    }))
+}
+
+/// 一条记录展开后的所有行。join 方式只有一行，explode 方式每个数组元素一行
+fn to_rows(record: &Value, options: &CsvWriteOptions) -> Vec<Vec<(String, Value)>> {
+    let mut row = Vec::new();
+    match record {
+        Value::Object(_) => flatten(record, "", &mut row),
+        // 不是对象的记录放到 value 列
+        value => row.push(("value".to_string(), value.clone())),
+    }
+    match options.arrays {
+        ArrayMode::Join => {
+            let row = row
+                .into_iter()
+                .map(|(name, value)| (name, join(value, &options.separator)))
+                .collect();
+            vec![row]
+        }
+        ArrayMode::Explode => explode(row),
+    }
+}
+
+fn flatten(value: &Value, prefix: &str, row: &mut Vec<(String, Value)>) {
+    match value {
+        // 顶层的空对象是没有字段的一行，嵌套的空对象保留为 {} 单元格
+        Value::Object(map) if !map.is_empty() || prefix.is_empty() => {
+            for (name, value) in map {
+                let path = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                flatten(value, &path, row);
+            }
+        }
+        value => row.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn join(value: Value, separator: &str) -> Value {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| match item {
+                    Value::Array(_) | Value::Object(_) => item.to_string(),
+                    item => cell(item),
+                })
+                .collect();
+            Value::String(items.join(separator))
+        }
+        value => value,
+    }
+}
+
+/// 把第一个数组字段的每个元素展开成一行，再递归处理剩下的数组
+fn explode(row: Vec<(String, Value)>) -> Vec<Vec<(String, Value)>> {
+    let Some(index) = row.iter().position(|(_, value)| value.is_array()) else {
+        return vec![row];
+    };
+    let (path, items) = match &row[index] {
+        (path, Value::Array(items)) => (path.clone(), items.clone()),
+        _ => unreachable!(),
+    };
+    if items.is_empty() {
+        let mut row = row;
+        row[index].1 = Value::Null;
+        return explode(row);
+    }
+
+    let mut rows = Vec::new();
+    for item in &items {
+        let mut expanded = row[..index].to_vec();
+        flatten(item, &path, &mut expanded);
+        expanded.extend_from_slice(&row[index + 1..]);
+        rows.extend(explode(expanded));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn records() -> Vec<Value> {
+        vec![
+            json!({"id": 1, "user": {"name": "Tom", "address": {"city": "Paris"}}, "tags": ["a", "b"]}),
+            json!({"id": 2, "user": {"name": "Jerry"}, "tags": [], "extra": null}),
+        ]
+    }
+
+    #[test]
+    fn test_to_csv_join() -> anyhow::Result<()> {
+        let csv = to_csv(&records(), &CsvWriteOptions::default())?;
+        let expected = "\
+id,user.name,user.address.city,tags,extra
+1,Tom,Paris,a;b,
+2,Jerry,,,
+";
+        assert_eq!(csv, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_csv_columns() -> anyhow::Result<()> {
+        let options = CsvWriteOptions {
+            columns: Some(vec!["user.name".to_string(), "id".to_string()]),
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        let columns = options.columns.as_deref().unwrap();
+        write_csv(
+            &mut output,
+            records().into_iter().map(Ok),
+            columns,
+            &options,
+        )?;
+        assert_eq!(String::from_utf8(output)?, "user.name,id\nTom,1\nJerry,2\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_csv_empty_object() -> anyhow::Result<()> {
+        let records = vec![json!({}), json!({"a": 1, "b": {}})];
+        let csv = to_csv(&records, &CsvWriteOptions::default())?;
+        assert_eq!(csv, "a,b\n,\n1,{}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_csv_explode() -> anyhow::Result<()> {
+        let options = CsvWriteOptions {
+            delimiter: b'\t',
+            arrays: ArrayMode::Explode,
+            ..Default::default()
+        };
+        let records = vec![
+            json!({"id": 1, "items": [{"sku": "x", "qty": 2}, {"sku": "y"}], "tags": ["a", "b"]}),
+        ];
+        let csv = to_csv(&records, &options)?;
+        let expected = "\
+id\titems.sku\titems.qty\ttags
+1\tx\t2\ta
+1\tx\t2\tb
+1\ty\t\ta
+1\ty\t\tb
+";
+        assert_eq!(csv, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+/// schema 文件里可以使用的列类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum ColumnType {
+    String,
+    Integer,
+    Float,
+    Bool,
+    Date,
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ColumnType::String => "string",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::Bool => "bool",
+            ColumnType::Date => "date",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 把CSV里的文本转换成对应类型的值
+#[derive(Debug, Default)]
+pub(super) struct TypeInference {
+    /// 是否推断没有在 schema 中指定的列
+    infer: bool,
+    /// 推断时是否识别日期
+    dates: bool,
+    schema: HashMap<String, ColumnType>,
+}
+
+/// 推断日期时接受的格式，统一输出为 YYYY-MM-DD。RFC3339 时间保持原文不变
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"];
+
+impl TypeInference {
+    /// schema 文件是列名到类型的映射，支持 JSON 和 YAML，例如 {"Index": "integer"}
+    pub(super) fn new(infer: bool, dates: bool, schema: Option<&str>) -> anyhow::Result<Self> {
+        let schema = match schema {
+            Some(path) => serde_yaml::from_str(&fs::read_to_string(path)?)
+                .map_err(|e| anyhow::anyhow!("无效的schema文件{}: {}", path, e))?,
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            infer,
+            dates,
+            schema,
+        })
+    }
+
+    pub(super) fn convert(&self, column: &str, value: &str) -> anyhow::Result<Value> {
+        match self.schema.get(column) {
+            Some(column_type) => convert_as(*column_type, value).ok_or_else(|| {
+                anyhow::anyhow!("列{}的值\"{}\"不是{}类型", column, value, column_type)
+            }),
+            None if self.infer => Ok(self.infer_value(value)),
+            None => Ok(Value::String(value.to_string())),
+        }
+    }
+
+    fn infer_value(&self, value: &str) -> Value {
+        if value.is_empty() {
+            return Value::Null;
+        }
+        let mut types = vec![ColumnType::Bool, ColumnType::Integer, ColumnType::Float];
+        if self.dates {
+            types.push(ColumnType::Date);
+        }
+        // 超出i64/u64范围的纯数字(如ICCID)转成浮点会丢失精度，保留为字符串
+        let integer_like = !value.contains(['.', 'e', 'E']);
+        types
+            .into_iter()
+            .filter(|column_type| !(integer_like && *column_type == ColumnType::Float))
+            .find_map(|column_type| convert_as(column_type, value))
+            .unwrap_or_else(|| Value::String(value.to_string()))
+    }
+}
+
+/// 按指定类型转换，转换失败时返回 None。空单元格转换为 null
+fn convert_as(column_type: ColumnType, value: &str) -> Option<Value> {
+    if value.is_empty() && column_type != ColumnType::String {
+        return Some(Value::Null);
+    }
+    match column_type {
+        ColumnType::String => Some(Value::String(value.to_string())),
+        ColumnType::Bool => match value.to_lowercase().as_str() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ColumnType::Integer if is_number(value) && !value.contains(['.', 'e', 'E']) => value
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| value.parse::<u64>().map(Value::from))
+            .ok(),
+        ColumnType::Integer => None,
+        ColumnType::Float if is_number(value) => value
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        ColumnType::Float => None,
+        ColumnType::Date => parse_date(value).map(Value::String),
+    }
+}
+
+/// 只接受普通的十进制写法。以0开头的多位数(邮编、电话号码)保留为字符串
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let valid_chars = digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'));
+    let leading_zero = digits.len() > 1
+        && digits.starts_with('0')
+        && !digits.starts_with("0.")
+        && !digits.starts_with("0e")
+        && !digits.starts_with("0E");
+    digits.starts_with(|c: char| c.is_ascii_digit()) && valid_chars && !leading_zero
+}
+
+fn parse_date(value: &str) -> Option<String> {
+    if DateTime::parse_from_rfc3339(value).is_ok() {
+        return Some(value.to_string());
+    }
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_infer_value() -> anyhow::Result<()> {
+        let inference = TypeInference::new(true, false, None)?;
+        let cases = [
+            ("1", json!(1)),
+            ("-42", json!(-42)),
+            ("18446744073709551615", json!(18446744073709551615u64)),
+            ("89014103211118510720", json!("89014103211118510720")),
+            ("-9223372036854775809", json!("-9223372036854775809")),
+            ("2.5", json!(2.5)),
+            ("1e3", json!(1000.0)),
+            ("TRUE", json!(true)),
+            ("", Value::Null),
+            ("007", json!("007")),
+            ("0.5", json!(0.5)),
+            ("NaN", json!("NaN")),
+            ("inf", json!("inf")),
+            ("1945-10-26", json!("1945-10-26")),
+            ("88F7B33d2bcf9f5", json!("88F7B33d2bcf9f5")),
+            ("001-084-906-7849x73518", json!("001-084-906-7849x73518")),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(inference.convert("col", value)?, expected, "{}", value);
+        }
+
+        let inference = TypeInference::new(true, true, None)?;
+        assert_eq!(inference.convert("col", "1945/10/26")?, json!("1945-10-26"));
+        let datetime = "2024-01-02T03:04:05.120Z";
+        assert_eq!(inference.convert("col", datetime)?, json!(datetime));
+
+        let inference = TypeInference::new(false, false, None)?;
+        assert_eq!(inference.convert("col", "1")?, json!("1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema() -> anyhow::Result<()> {
+        let schema = crate::utils::temp_path("csv_schema.yaml");
+        fs::write(&schema, "Phone: string\nScore: float\nBirth: date\n")?;
+        let inference = TypeInference::new(true, false, schema.to_str())?;
+
+        assert_eq!(inference.convert("Phone", "007")?, json!("007"));
+        assert_eq!(inference.convert("Phone", "12")?, json!("12"));
+        assert_eq!(inference.convert("Score", "12")?, json!(12.0));
+        assert_eq!(inference.convert("Score", "")?, Value::Null);
+        assert_eq!(
+            inference.convert("Birth", "2024.01.02")?,
+            json!("2024-01-02")
+        );
+        assert!(inference.convert("Score", "abc").is_err());
+        assert!(inference.convert("Birth", "yesterday").is_err());
+
+        fs::write(&schema, "Phone: text\n")?;
+        assert!(TypeInference::new(true, false, schema.to_str()).is_err());
+        Ok(())
+    }
+}
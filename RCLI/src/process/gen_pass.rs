@@ -42,11 +42,16 @@ pub fn process_gen_pass(opts: &GenPassOpts) -> anyhow::Result<()> {
     let string_pass = String::from_iter(password);
 
     // 密码强度估计
-    let estimate = zxcvbn(&string_pass, &[]);
+    let score = password_strength(&string_pass);
 
     print!("{}", string_pass);
 
-    log::error!("密码强度: {}", estimate.score());
+    log::error!("密码强度: {}", score);
 
     Ok(())
 }
+
+/// 使用zxcvbn估计密码强度，返回0~4的分数，分数越高越安全
+pub(crate) fn password_strength(password: &str) -> u8 {
+    zxcvbn(password, &[]).score().into()
+}
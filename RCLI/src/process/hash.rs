@@ -0,0 +1,181 @@
+use crate::get_reader;
+use crate::opts::hash::{HashAlgorithm, HashOpts, HashOutputFormat};
+use crate::utils::read_chunks;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use sha2::Digest;
+use std::io::Read;
+
+pub fn process_hash(opts: &HashOpts) -> anyhow::Result<()> {
+    match &opts.check {
+        Some(sums_file) => {
+            let failed = process_hash_check(sums_file, opts.algo, opts.format)?;
+            if failed > 0 {
+                anyhow::bail!("WARNING: {} computed checksum(s) did NOT match", failed);
+            }
+        }
+        None => {
+            let hash = hash_input(&opts.input, opts.algo)?;
+            // 与 sha256sum 的输出格式保持一致，方便使用 --check 校验
+            println!("{}  {}", encode_hash(&hash, opts.format), opts.input);
+        }
+    }
+    Ok(())
+}
+
+/// 按 `sha256sum -c` 的方式逐行校验，返回校验失败的文件数
+fn process_hash_check(
+    sums_file: &str,
+    algo: HashAlgorithm,
+    format: HashOutputFormat,
+) -> anyhow::Result<usize> {
+    let mut content = String::new();
+    get_reader(sums_file)?.read_to_string(&mut content)?;
+
+    let mut failed = 0;
+    for (lineno, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // 格式为 "<hash>  <file>"，二进制模式为 "<hash> *<file>"
+        let Some((expected, file)) = line.split_once(' ') else {
+            // 与 sha256sum 一致，格式错误的行只给出警告并跳过
+            eprintln!("{}: 第{}行格式不正确，已跳过", sums_file, lineno + 1);
+            continue;
+        };
+        let file = file.strip_prefix([' ', '*']).unwrap_or(file);
+
+        let matched = match hash_input(file, algo) {
+            Ok(hash) => encode_hash(&hash, format).eq_ignore_ascii_case(expected),
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                false
+            }
+        };
+        if matched {
+            println!("{}: OK", file);
+        } else {
+            println!("{}: FAILED", file);
+            failed += 1;
+        }
+    }
+    Ok(failed)
+}
+
+fn hash_input(input: &str, algo: HashAlgorithm) -> anyhow::Result<Vec<u8>> {
+    let mut reader = get_reader(input)?;
+    let mut hasher = Hasher::new(algo);
+    read_chunks(&mut reader, |buf| hasher.update(buf))?;
+    Ok(hasher.finalize())
+}
+
+fn encode_hash(hash: &[u8], format: HashOutputFormat) -> String {
+    match format {
+        HashOutputFormat::Hex => hex::encode(hash),
+        HashOutputFormat::Base64 => BASE64_STANDARD.encode(hash),
+    }
+}
+
+/// 统一各哈希算法的增量计算接口
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Sha1(sha1::Sha1),
+    Md5(md5::Md5),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgorithm) -> Self {
+        match algo {
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+            HashAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha512 => Self::Sha512(sha2::Sha512::new()),
+            HashAlgorithm::Sha1 => Self::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Md5 => Self::Md5(md5::Md5::new()),
+            HashAlgorithm::Crc32 => Self::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Md5(hasher) => hasher.update(data),
+            Self::Crc32(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+            Self::Sha512(hasher) => hasher.finalize().to_vec(),
+            Self::Sha1(hasher) => hasher.finalize().to_vec(),
+            Self::Md5(hasher) => hasher.finalize().to_vec(),
+            Self::Crc32(hasher) => hasher.finalize().to_be_bytes().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(algo: HashAlgorithm, data: &[u8]) -> String {
+        let mut hasher = Hasher::new(algo);
+        hasher.update(data);
+        encode_hash(&hasher.finalize(), HashOutputFormat::Hex)
+    }
+
+    #[test]
+    fn test_hash_known_values() {
+        assert_eq!(
+            digest(HashAlgorithm::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digest(HashAlgorithm::Sha1, b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            digest(HashAlgorithm::Md5, b"abc"),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(digest(HashAlgorithm::Crc32, b"123456789"), "cbf43926");
+        assert_eq!(
+            digest(HashAlgorithm::Blake3, b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn test_hash_check() -> anyhow::Result<()> {
+        let input = "assets/blake3.txt";
+        let hash = encode_hash(
+            &hash_input(input, HashAlgorithm::Sha256)?,
+            HashOutputFormat::Hex,
+        );
+        let sums_file = crate::utils::temp_path("hash_check.sha256");
+        std::fs::write(
+            &sums_file,
+            format!(
+                "{hash}  {input}\n{hash} *{input}\n{}  {input}\n",
+                "0".repeat(64)
+            ),
+        )?;
+
+        let failed = process_hash_check(
+            sums_file.to_str().unwrap(),
+            HashAlgorithm::Sha256,
+            HashOutputFormat::Hex,
+        )?;
+        assert_eq!(failed, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::get_reader;
+use crate::opts::hexdump::HexdumpOpts;
+use crate::utils::get_writer;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+/// 还原时行之间的空缺最多填充的字节数，避免构造的偏移量导致写出巨大的文件
+const MAX_GAP: u64 = 16 * 1024 * 1024;
+
+pub fn process_hexdump(opts: &HexdumpOpts) -> anyhow::Result<()> {
+    let mut reader = get_reader(&opts.input)?;
+    let mut writer = BufWriter::new(get_writer(opts.output.as_deref())?);
+    if opts.reverse {
+        process_unhexdump(&mut reader, &mut writer)?;
+    } else {
+        // stdin 无法 seek，统一通过读取并丢弃的方式跳过开头
+        let skipped = std::io::copy(&mut reader.by_ref().take(opts.offset), &mut std::io::sink())?;
+        if skipped < opts.offset {
+            anyhow::bail!("偏移量{}超出了内容长度{}", opts.offset, skipped);
+        }
+        let mut reader = reader.take(opts.length.unwrap_or(u64::MAX));
+        process_hexdump_dump(&mut reader, &mut writer, opts.offset, opts.cols, opts.group)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 输出格式与 xxd 一致: "00000000: 4865 6c6c 6f0a                           Hello."
+fn process_hexdump_dump(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    offset: u64,
+    cols: usize,
+    group: usize,
+) -> anyhow::Result<()> {
+    // 一整行十六进制部分的宽度，用于对齐最后一行的 ASCII 部分
+    let hex_width = cols * 2 + cols.div_ceil(group) - 1;
+    let mut buf = vec![0u8; cols];
+    let mut offset = offset;
+    loop {
+        let n = read_full(reader, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let line = &buf[..n];
+
+        let mut hex = String::with_capacity(hex_width);
+        for (i, chunk) in line.chunks(group).enumerate() {
+            if i > 0 {
+                hex.push(' ');
+            }
+            hex.push_str(&hex::encode(chunk));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(writer, "{:08x}: {:<hex_width$}  {}", offset, hex, ascii)?;
+
+        offset += n as u64;
+        if n < cols {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 把 xxd 格式的内容还原成二进制。行之间的偏移量有空缺时用 0 填充，空缺不能超过 MAX_GAP
+fn process_unhexdump(reader: &mut dyn Read, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let reader = BufReader::new(reader);
+    // 第一行的偏移量作为起点，与 xxd -s 导出的内容对应
+    let mut position: Option<u64> = None;
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || anyhow::anyhow!("第{}行不是有效的 xxd 格式: {}", lineno + 1, line);
+
+        let (offset, rest) = line.split_once(':').ok_or_else(invalid)?;
+        let offset = u64::from_str_radix(offset.trim(), 16).map_err(|_| invalid())?;
+        // 十六进制部分与 ASCII 部分之间至少有两个空格
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        let hex_part = rest.split_once("  ").map_or(rest, |(hex, _)| hex);
+        let digits: String = hex_part.split_whitespace().collect();
+        let bytes = hex::decode(&digits).map_err(|_| invalid())?;
+
+        let current = *position.get_or_insert(offset);
+        if offset < current {
+            anyhow::bail!(
+                "第{}行的偏移量{:08x}小于已写出的位置{:08x}",
+                lineno + 1,
+                offset,
+                current
+            );
+        }
+        let gap = offset - current;
+        if gap > MAX_GAP {
+            anyhow::bail!(
+                "第{}行的偏移量{:08x}与上一行相差{}字节，超过了{}字节的上限",
+                lineno + 1,
+                offset,
+                gap,
+                MAX_GAP
+            );
+        }
+        std::io::copy(&mut std::io::repeat(0).take(gap), writer)?;
+        writer.write_all(&bytes)?;
+        position = Some(offset + bytes.len() as u64);
+    }
+    Ok(())
+}
+
+/// 尽量读满 buf，只有读到结尾时才返回比 buf 短的长度
+fn read_full(reader: &mut dyn Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump(data: &[u8], offset: u64, cols: usize, group: usize) -> String {
+        let mut output = Vec::new();
+        process_hexdump_dump(&mut &data[..], &mut output, offset, cols, group).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn undump(text: &str) -> anyhow::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        process_unhexdump(&mut text.as_bytes(), &mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_hexdump_layout() {
+        let data = b"Hello, hexdump!\nrcli\x00\xff";
+        let expected = "\
+00000000: 4865 6c6c 6f2c 2068 6578 6475 6d70 210a  Hello, hexdump!.
+00000010: 7263 6c69 00ff                           rcli..
+";
+        assert_eq!(dump(data, 0, 16, 2), expected);
+
+        let expected = "00000010: 48656c6c 6f  Hello\n";
+        assert_eq!(dump(b"Hello", 16, 5, 4), expected);
+    }
+
+    #[test]
+    fn test_hexdump_reverse() -> anyhow::Result<()> {
+        let data: Vec<u8> = (0..=255).collect();
+        for (cols, group) in [(16, 2), (8, 1), (32, 4), (7, 3)] {
+            assert_eq!(undump(&dump(&data, 0, cols, group))?, data);
+        }
+
+        // ASCII 部分里的十六进制字符不会被当成内容
+        assert_eq!(undump("00000000: 6162  ab12\n")?, b"ab");
+        // 偏移量有空缺时用 0 填充
+        assert_eq!(undump("00000010: 01\n00000014: 02\n")?, [1, 0, 0, 0, 2]);
+        assert!(undump("00000010: 01\n00000000: 02\n").is_err());
+        // 空缺过大时报错，而不是写出大量的 0
+        assert!(undump("00000000: 00\nffffffff0: 00\n").is_err());
+        assert!(undump("not a dump\n").is_err());
+        Ok(())
+    }
+}
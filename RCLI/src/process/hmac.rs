@@ -0,0 +1,232 @@
+use crate::opts::hash::HashOutputFormat;
+use crate::opts::hmac::{HmacAlgorithm, HmacKeyFormat};
+use crate::utils::read_chunks;
+use crate::{HmacSubCommand, get_reader};
+use base64::Engine;
+use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE};
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Sha512};
+use std::io::Read;
+
+pub fn process_hmac(sub_command: &HmacSubCommand) -> anyhow::Result<()> {
+    match sub_command {
+        HmacSubCommand::Sign(opts) => {
+            let key = load_key(
+                opts.key.as_deref(),
+                opts.key_file.as_deref(),
+                opts.key_env.as_deref(),
+                opts.key_format,
+            )?;
+            let mut reader = get_reader(&opts.input)?;
+            let sig = process_hmac_sign(&mut reader, &key, opts.alg)?;
+            let sig = match opts.format {
+                HashOutputFormat::Hex => hex::encode(sig),
+                HashOutputFormat::Base64 => BASE64_STANDARD.encode(sig),
+            };
+            println!("{}", sig);
+        }
+        HmacSubCommand::Verify(opts) => {
+            let key = load_key(
+                opts.key.as_deref(),
+                opts.key_file.as_deref(),
+                opts.key_env.as_deref(),
+                opts.key_format,
+            )?;
+            let sig = decode_sig(&opts.sig, opts.alg, opts.format)?;
+            let mut reader = get_reader(&opts.input)?;
+            if !process_hmac_verify(&mut reader, &key, opts.alg, &sig)? {
+                anyhow::bail!("HMAC验证失败");
+            }
+            println!("HMAC验证成功");
+        }
+    }
+    Ok(())
+}
+
+fn process_hmac_sign(
+    reader: &mut dyn Read,
+    key: &[u8],
+    alg: HmacAlgorithm,
+) -> anyhow::Result<Vec<u8>> {
+    let sig = match alg {
+        HmacAlgorithm::Sha256 => mac_reader::<Hmac<Sha256>>(reader, key)?
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+        HmacAlgorithm::Sha512 => mac_reader::<Hmac<Sha512>>(reader, key)?
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+    };
+    Ok(sig)
+}
+
+fn process_hmac_verify(
+    reader: &mut dyn Read,
+    key: &[u8],
+    alg: HmacAlgorithm,
+    sig: &[u8],
+) -> anyhow::Result<bool> {
+    let verified = match alg {
+        HmacAlgorithm::Sha256 => mac_reader::<Hmac<Sha256>>(reader, key)?
+            .verify_slice(sig)
+            .is_ok(),
+        HmacAlgorithm::Sha512 => mac_reader::<Hmac<Sha512>>(reader, key)?
+            .verify_slice(sig)
+            .is_ok(),
+    };
+    Ok(verified)
+}
+
+fn mac_reader<M: Mac + hmac::digest::KeyInit>(
+    reader: &mut dyn Read,
+    key: &[u8],
+) -> anyhow::Result<M> {
+    let mut mac = <M as Mac>::new_from_slice(key)?;
+    read_chunks(reader, |buf| mac.update(buf))?;
+    Ok(mac)
+}
+
+/// 密钥可以来自命令行参数、文件或环境变量，由clap保证只指定其中一个
+fn load_key(
+    key: Option<&str>,
+    key_file: Option<&str>,
+    key_env: Option<&str>,
+    format: HmacKeyFormat,
+) -> anyhow::Result<Vec<u8>> {
+    if let Some(key) = key {
+        return decode_key(key, format);
+    }
+    if let Some(name) = key_env {
+        let key = std::env::var(name).map_err(|_| anyhow::anyhow!("无法读取环境变量: {}", name))?;
+        return decode_key(&key, format);
+    }
+    let path = key_file.ok_or_else(|| anyhow::anyhow!("需要指定密钥"))?;
+    let mut key = Vec::new();
+    get_reader(path)?.read_to_end(&mut key)?;
+    match format {
+        // 二进制密钥只去掉编辑器或 echo 添加的换行
+        HmacKeyFormat::Raw => {
+            while key.last().is_some_and(|b| matches!(b, b'\n' | b'\r')) {
+                key.pop();
+            }
+            if key.is_empty() {
+                anyhow::bail!("密钥不能为空");
+            }
+            Ok(key)
+        }
+        _ => decode_key(std::str::from_utf8(&key)?, format),
+    }
+}
+
+fn decode_key(key: &str, format: HmacKeyFormat) -> anyhow::Result<Vec<u8>> {
+    let key = match format {
+        HmacKeyFormat::Raw => key.as_bytes().to_vec(),
+        HmacKeyFormat::Hex => hex::decode(key.trim())?,
+        HmacKeyFormat::Base64 => decode_base64(key.trim())?,
+    };
+    if key.is_empty() {
+        anyhow::bail!("密钥不能为空");
+    }
+    Ok(key)
+}
+
+/// 解析期望的HMAC。GitHub 等服务会在签名前加上 "sha256=" 前缀
+fn decode_sig(sig: &str, alg: HmacAlgorithm, format: HashOutputFormat) -> anyhow::Result<Vec<u8>> {
+    let sig = sig.trim();
+    let prefix = format!("{}=", alg);
+    let sig = sig.strip_prefix(prefix.as_str()).unwrap_or(sig);
+    let sig = match format {
+        HashOutputFormat::Hex => hex::decode(sig)?,
+        HashOutputFormat::Base64 => decode_base64(sig)?,
+    };
+    Ok(sig)
+}
+
+fn decode_base64(data: &str) -> anyhow::Result<Vec<u8>> {
+    BASE64_STANDARD
+        .decode(data)
+        .or_else(|_| BASE64_URL_SAFE.decode(data))
+        .map_err(|e| anyhow::anyhow!("无效的base64: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_hmac_rfc4231() -> anyhow::Result<()> {
+        // RFC 4231 测试用例 2
+        let key = decode_key("Jefe", HmacKeyFormat::Raw)?;
+        let data = b"what do ya want for nothing?";
+        let sig = process_hmac_sign(&mut &data[..], &key, HmacAlgorithm::Sha256)?;
+        assert_eq!(
+            hex::encode(&sig),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let sig = decode_sig(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            HmacAlgorithm::Sha256,
+            HashOutputFormat::Hex,
+        )?;
+        assert!(process_hmac_verify(
+            &mut &data[..],
+            &key,
+            HmacAlgorithm::Sha256,
+            &sig
+        )?);
+        assert!(!process_hmac_verify(
+            &mut &b"tampered"[..],
+            &key,
+            HmacAlgorithm::Sha256,
+            &sig
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmac_key_formats() -> anyhow::Result<()> {
+        let raw = decode_key("Jefe", HmacKeyFormat::Raw)?;
+        assert_eq!(decode_key("4a656665", HmacKeyFormat::Hex)?, raw);
+        assert_eq!(decode_key("SmVmZQ==", HmacKeyFormat::Base64)?, raw);
+        assert!(decode_key("zz", HmacKeyFormat::Hex).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmac_key_sources() -> anyhow::Result<()> {
+        let raw = decode_key("Jefe", HmacKeyFormat::Raw)?;
+
+        let key_file = crate::utils::temp_path("hmac_key_sources.txt");
+        fs::write(&key_file, "Jefe\n")?;
+        let key_file = key_file.to_str().unwrap();
+        assert_eq!(
+            load_key(None, Some(key_file), None, HmacKeyFormat::Raw)?,
+            raw
+        );
+        fs::write(key_file, "4a656665\n")?;
+        assert_eq!(
+            load_key(None, Some(key_file), None, HmacKeyFormat::Hex)?,
+            raw
+        );
+
+        // PATH 总是存在，用它代替设置环境变量
+        let path = std::env::var("PATH")?;
+        assert_eq!(
+            load_key(None, None, Some("PATH"), HmacKeyFormat::Raw)?,
+            path.into_bytes()
+        );
+        assert!(
+            load_key(
+                None,
+                None,
+                Some("RCLI_HMAC_KEY_NOT_SET"),
+                HmacKeyFormat::Raw
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::opts::jwt::JwtAlgorithm;
+use crate::process::text::keys::{parse_signing_key, parse_verifying_key};
+use crate::{JwtSubCommand, get_reader};
+use base64::Engine;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use chrono::Utc;
+use ed25519_dalek::{Signature, Signer, Verifier};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::fs;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub fn process_jwt(sub_command: &JwtSubCommand) -> anyhow::Result<()> {
+    match sub_command {
+        JwtSubCommand::Sign(opts) => {
+            let token = process_jwt_sign(&opts.sub, &opts.aud, opts.exp, &opts.key, opts.alg)?;
+            println!("{}", token);
+        }
+        JwtSubCommand::Verify(opts) => {
+            let mut token = String::new();
+            get_reader(&opts.token)?.read_to_string(&mut token)?;
+            let claims = process_jwt_verify(&token, &opts.key, opts.alg, opts.aud.as_deref())?;
+            println!("{}", serde_json::to_string_pretty(&claims)?);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    /// RFC 7519 中 typ 是可选的，其他签发方生成的token可能没有
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+}
+
+fn process_jwt_sign(
+    sub: &str,
+    aud: &str,
+    exp: u64,
+    key: &str,
+    alg: JwtAlgorithm,
+) -> anyhow::Result<String> {
+    let header = Header {
+        alg: alg.to_string(),
+        typ: Some("JWT".to_string()),
+    };
+    let now = Utc::now().timestamp();
+    let claims = json!({
+        "sub": sub,
+        "aud": aud,
+        "iat": now,
+        "exp": now.saturating_add_unsigned(exp),
+    });
+    let signing_input = format!(
+        "{}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+        BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?)
+    );
+
+    let key = fs::read(key)?;
+    let sig = match alg {
+        JwtAlgorithm::Hs256 => {
+            let mut mac = HmacSha256::new_from_slice(&key)?;
+            mac.update(signing_input.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+        JwtAlgorithm::EdDsa => {
+            let key = parse_signing_key(&key)?;
+            key.sign(signing_input.as_bytes()).to_bytes().to_vec()
+        }
+    };
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        BASE64_URL_SAFE_NO_PAD.encode(sig)
+    ))
+}
+
+/// 验证签名、过期时间和受众，返回token中的claims
+fn process_jwt_verify(
+    token: &str,
+    key: &str,
+    alg: JwtAlgorithm,
+    aud: Option<&str>,
+) -> anyhow::Result<Value> {
+    let token = token.trim();
+    let (signing_input, sig) = token
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow::anyhow!("无效的token格式"))?;
+    let (header, claims) = signing_input
+        .split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("无效的token格式"))?;
+
+    let header: Header = serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(header)?)?;
+    // 只接受指定的算法，避免算法混淆攻击
+    if header.alg != alg.to_string() {
+        anyhow::bail!("token的算法为{}，期望的算法为{}", header.alg, alg);
+    }
+
+    let sig = BASE64_URL_SAFE_NO_PAD.decode(sig)?;
+    let key = fs::read(key)?;
+    let verified = match alg {
+        JwtAlgorithm::Hs256 => {
+            let mut mac = HmacSha256::new_from_slice(&key)?;
+            mac.update(signing_input.as_bytes());
+            // verify_slice 是常量时间比较
+            mac.verify_slice(&sig).is_ok()
+        }
+        JwtAlgorithm::EdDsa => {
+            let key = parse_verifying_key(&key)?;
+            let sig = Signature::from_slice(&sig)?;
+            key.verify(signing_input.as_bytes(), &sig).is_ok()
+        }
+    };
+    if !verified {
+        anyhow::bail!("token签名验证失败");
+    }
+
+    let claims: Value = serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(claims)?)?;
+    if let Some(exp) = claims.get("exp") {
+        let exp = exp
+            .as_i64()
+            .ok_or_else(|| anyhow::anyhow!("无效的exp: {}", exp))?;
+        if exp <= Utc::now().timestamp() {
+            anyhow::bail!("token已过期");
+        }
+    }
+    if let Some(aud) = aud {
+        // aud 可以是字符串或字符串数组
+        let matched = match claims.get("aud") {
+            Some(Value::String(value)) => value == aud,
+            Some(Value::Array(values)) => values.iter().any(|v| v.as_str() == Some(aud)),
+            _ => false,
+        };
+        if !matched {
+            anyhow::bail!("token的aud与期望的{}不匹配", aud);
+        }
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "assets/blake3.txt";
+
+    #[test]
+    fn test_jwt_hs256() -> anyhow::Result<()> {
+        let token = process_jwt_sign("alice", "device1", 3600, KEY, JwtAlgorithm::Hs256)?;
+        let claims = process_jwt_verify(&token, KEY, JwtAlgorithm::Hs256, Some("device1"))?;
+        assert_eq!(claims["sub"], "alice");
+
+        assert!(process_jwt_verify(&token, KEY, JwtAlgorithm::Hs256, Some("device2")).is_err());
+        assert!(process_jwt_verify(&token, KEY, JwtAlgorithm::EdDsa, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_eddsa() -> anyhow::Result<()> {
+        let token = process_jwt_sign("alice", "device1", 3600, KEY, JwtAlgorithm::EdDsa)?;
+
+        let verifying_key = crate::utils::temp_path("jwt_eddsa.pk");
+        let key = parse_signing_key(&fs::read(KEY)?)?;
+        fs::write(&verifying_key, key.verifying_key().as_bytes())?;
+        let verifying_key = verifying_key.to_str().unwrap();
+
+        let claims = process_jwt_verify(&token, verifying_key, JwtAlgorithm::EdDsa, None)?;
+        assert_eq!(claims["aud"], "device1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_header_without_typ() -> anyhow::Result<()> {
+        let signing_input = format!(
+            "{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256"}"#),
+            BASE64_URL_SAFE_NO_PAD.encode(r#"{"sub":"bob"}"#)
+        );
+        let mut mac = HmacSha256::new_from_slice(&fs::read(KEY)?)?;
+        mac.update(signing_input.as_bytes());
+        let sig = BASE64_URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        let token = format!("{}.{}", signing_input, sig);
+
+        let claims = process_jwt_verify(&token, KEY, JwtAlgorithm::Hs256, None)?;
+        assert_eq!(claims["sub"], "bob");
+        Ok(())
+    }
+
+    #[test]
+    fn test_jwt_expired() -> anyhow::Result<()> {
+        let token = process_jwt_sign("alice", "device1", 0, KEY, JwtAlgorithm::Hs256)?;
+        let err = process_jwt_verify(&token, KEY, JwtAlgorithm::Hs256, None).unwrap_err();
+        assert!(err.to_string().contains("过期"));
+        Ok(())
+    }
+}
@@ -1,53 +1,557 @@
-use crate::opts::text::TextSignFormat;
+pub(crate) mod keys;
+mod manifest;
+mod sshsig;
+
+use crate::opts::base64::Base64Format;
+use crate::opts::text::{TextSignFormat, TextVerifyOpts};
+use crate::process::gen_pass::password_strength;
+use crate::utils::read_chunks;
 use crate::{TextSubCommand, get_reader};
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::Engine;
-use base64::prelude::BASE64_URL_SAFE_NO_PAD;
-use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Digest, Sha512, Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use manifest::{process_text_sign_manifest, process_text_verify_manifest};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sshsig::{SSHSIG_PEM, SshSigSigner, SshSigVerifier};
 use std::fs;
-use std::io::Read;
-use tracing::log;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const NONCE_LEN: usize = 12;
+// 与 ssh-keygen -Y sign -n file 的默认用法保持一致
+const DEFAULT_NAMESPACE: &str = "file";
+const SALT_LEN: usize = 16;
+const PASSWORD_MAGIC: &[u8] = b"RCLIPW1";
+// zxcvbn 评分低于该值时提示密码强度不足
+const MIN_PASSWORD_SCORE: u8 = 3;
 
 pub fn process_text(sub_command: &TextSubCommand) -> anyhow::Result<()> {
     match sub_command {
-        TextSubCommand::Sign(opts) => {
-            process_text_sign(&opts.input, &opts.key, opts.format)?;
+        TextSubCommand::Sign(opts) => match &opts.manifest {
+            Some(dir) => {
+                let manifest = process_text_sign_manifest(
+                    dir,
+                    &opts.key,
+                    opts.format,
+                    &opts.namespace,
+                    opts.output.as_deref(),
+                )?;
+                let content = serde_json::to_string_pretty(&manifest)?;
+                match &opts.output {
+                    Some(output) => {
+                        fs::write(output, content)?;
+                        println!("清单已写入: {}", output.display());
+                    }
+                    None => println!("{}", content),
+                }
+            }
+            None => {
+                let document =
+                    process_text_sign(&opts.input, &opts.key, opts.format, &opts.namespace)?;
+                match &opts.output {
+                    Some(output) => {
+                        // SSH签名直接写入 armored 格式，与 ssh-keygen -Y verify 兼容
+                        let content = match opts.format {
+                            TextSignFormat::SshSig => document.signature,
+                            _ => serde_json::to_string_pretty(&document)?,
+                        };
+                        fs::write(output, content)?;
+                        println!("签名已写入: {}", output.display());
+                    }
+                    None => println!("{}", document.signature.trim_end()),
+                }
+            }
+        },
+        TextSubCommand::Verify(opts) => match &opts.manifest {
+            Some(dir) => {
+                let sig_file = opts
+                    .sig_file
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("需要通过 --sig-file 指定清单文件"))?;
+                let format = opts.format.ok_or_else(|| {
+                    anyhow::anyhow!("使用清单验证时需要通过 --format 指定签名算法")
+                })?;
+                let report = process_text_verify_manifest(
+                    dir,
+                    &VerifyKey::from_opts(opts),
+                    sig_file,
+                    format,
+                )?;
+                for path in &report.added {
+                    println!("新增: {}", path);
+                }
+                for path in &report.missing {
+                    println!("缺失: {}", path);
+                }
+                for path in &report.modified {
+                    println!("修改: {}", path);
+                }
+                if !report.is_clean() {
+                    anyhow::bail!("目录与清单不一致");
+                }
+                println!("目录与清单一致");
+            }
+            None => {
+                let key = VerifyKey::from_opts(opts);
+                let verified = match (&opts.sig, &opts.sig_file) {
+                    (_, Some(sig_file)) => {
+                        let format = opts.format.ok_or_else(|| {
+                            anyhow::anyhow!("使用签名文件验证时需要通过 --format 指定签名算法")
+                        })?;
+                        process_text_verify_document(&opts.input, &key, sig_file, format)?
+                    }
+                    (Some(sig), None) => {
+                        let format = opts.format.unwrap_or(TextSignFormat::Blake3);
+                        process_text_verify(&opts.input, &key, sig, format)?
+                    }
+                    (None, None) => anyhow::bail!("需要指定签名或签名文件"),
+                };
+                if !verified {
+                    anyhow::bail!("签名验证失败");
+                }
+                println!("签名验证成功");
+            }
+        },
+        TextSubCommand::Generate(opts) => {
+            let keys = process_text_generate(opts.format)?;
+            for (name, content) in keys {
+                let path = opts.output.join(name);
+                write_key_file(&path, &content)?;
+                println!("密钥已写入: {}", path.display());
+            }
         }
-        TextSubCommand::Verify(opts) => {
-            log::info!("{:?}", opts);
+        TextSubCommand::Encrypt(opts) => {
+            let key = CipherKey::from_opts(
+                opts.key.as_deref(),
+                opts.password,
+                opts.password_env.as_deref(),
+                true,
+            )?;
+            let encrypted = process_text_encrypt(&opts.input, &key, opts.format)?;
+            println!("{}", encrypted);
+        }
+        TextSubCommand::Decrypt(opts) => {
+            let key = CipherKey::from_opts(
+                opts.key.as_deref(),
+                opts.password,
+                opts.password_env.as_deref(),
+                false,
+            )?;
+            let decrypted = process_text_decrypt(&opts.input, &key, opts.format)?;
+            std::io::stdout().write_all(&decrypted)?;
         }
     }
     Ok(())
 }
 
-fn process_text_sign(input: &str, key: &str, format: TextSignFormat) -> anyhow::Result<()> {
-    let mut reader = get_reader(input)?;
-    let signed = match format {
-        TextSignFormat::Blake3 => {
-            let signer = Blake3::load(key)?;
-            signer.sign(&mut reader)
+fn process_text_sign(
+    input: &str,
+    key: &str,
+    format: TextSignFormat,
+    namespace: &str,
+) -> anyhow::Result<SignatureDocument> {
+    let signer = load_signer(key, format, namespace)?;
+    let signed = signer.sign_input(input)?;
+
+    let (file_name, file_length) = if input == "-" {
+        ("-".to_string(), None)
+    } else {
+        let path = Path::new(input);
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| input.to_string());
+        (file_name, Some(fs::metadata(path)?.len()))
+    };
+
+    Ok(SignatureDocument {
+        algorithm: format,
+        key_id: signer.key_id(),
+        timestamp: Utc::now(),
+        file_name,
+        file_length,
+        signature: encode_signature(format, &signed),
+    })
+}
+
+fn process_text_verify(
+    input: &str,
+    key: &VerifyKey,
+    sig: &str,
+    format: TextSignFormat,
+) -> anyhow::Result<bool> {
+    let sig = decode_signature(format, sig)?;
+    let verifier = load_verifier(key, format)?;
+    verifier.verify_input(input, &sig)
+}
+
+/// 签名文件不可信，算法由调用方通过 `--format` 指定，文件中记录的算法必须与之一致。
+/// 否则可以用公钥作为blake3密钥伪造签名
+fn process_text_verify_document(
+    input: &str,
+    key: &VerifyKey,
+    sig_file: &str,
+    format: TextSignFormat,
+) -> anyhow::Result<bool> {
+    let content = fs::read_to_string(sig_file)?;
+    // ssh-keygen -Y sign 生成的签名文件
+    let algorithm = if content.trim_start().starts_with(SSHSIG_PEM) {
+        TextSignFormat::SshSig
+    } else {
+        serde_json::from_str::<SignatureDocument>(&content)?.algorithm
+    };
+    if algorithm != format {
+        anyhow::bail!(
+            "签名文件的算法({})与指定的算法({})不一致",
+            algorithm,
+            format
+        );
+    }
+    if format == TextSignFormat::SshSig {
+        return process_text_verify(input, key, &content, format);
+    }
+    let document: SignatureDocument = serde_json::from_str(&content)?;
+
+    let verifier = load_verifier(key, format)?;
+    if let Some(key_id) = verifier.key_id()
+        && key_id != document.key_id
+    {
+        anyhow::bail!(
+            "签名使用的密钥({})与提供的密钥({})不一致",
+            document.key_id,
+            key_id
+        );
+    }
+
+    if let Some(expected) = document.file_length
+        && input != "-"
+    {
+        let actual = fs::metadata(input)?.len();
+        if actual != expected {
+            eprintln!(
+                "文件长度不一致: 签名时为 {} 字节，当前为 {} 字节",
+                expected, actual
+            );
+            return Ok(false);
+        }
+    }
+
+    let sig = decode_signature(format, &document.signature)?;
+    verifier.verify_input(input, &sig)
+}
+
+fn load_signer(
+    key: &str,
+    format: TextSignFormat,
+    namespace: &str,
+) -> anyhow::Result<Box<dyn TextSign>> {
+    let signer: Box<dyn TextSign> = match format {
+        TextSignFormat::Blake3 => Box::new(Blake3::load(key)?),
+        TextSignFormat::Ed25519 => Box::new(Ed25519Signer::load(key)?),
+        TextSignFormat::Ed25519ph => Box::new(Ed25519ph::<Ed25519Signer>::load(key)?),
+        TextSignFormat::SshSig => Box::new(SshSigSigner::new(Ed25519Signer::load(key)?, namespace)),
+    };
+    Ok(signer)
+}
+
+fn load_verifier(key: &VerifyKey, format: TextSignFormat) -> anyhow::Result<Box<dyn TextVerify>> {
+    let verifier: Box<dyn TextVerify> = match format {
+        TextSignFormat::Blake3 => Box::new(Blake3::load(key.key()?)?),
+        TextSignFormat::Ed25519 => Box::new(Ed25519Verifier::load(key.key()?)?),
+        TextSignFormat::Ed25519ph => Box::new(Ed25519ph::<Ed25519Verifier>::load(key.key()?)?),
+        TextSignFormat::SshSig => Box::new(SshSigVerifier::new(
+            key.key.map(Ed25519Verifier::load).transpose()?,
+            key.allowed_signers,
+            key.identity,
+            key.namespace,
+        )?),
+    };
+    Ok(verifier)
+}
+
+/// SSH签名本身就是 armored 文本，其他签名使用 url-safe base64 编码
+fn encode_signature(format: TextSignFormat, sig: &[u8]) -> String {
+    match format {
+        TextSignFormat::SshSig => String::from_utf8_lossy(sig).to_string(),
+        _ => BASE64_URL_SAFE_NO_PAD.encode(sig),
+    }
+}
+
+fn decode_signature(format: TextSignFormat, sig: &str) -> anyhow::Result<Vec<u8>> {
+    match format {
+        TextSignFormat::SshSig => Ok(sig.as_bytes().to_vec()),
+        _ => Ok(BASE64_URL_SAFE_NO_PAD.decode(sig.trim())?),
+    }
+}
+
+/// 验证签名时使用的密钥。SSH签名也可以只通过 allowed_signers 确认签名者
+struct VerifyKey<'a> {
+    key: Option<&'a str>,
+    namespace: &'a str,
+    allowed_signers: Option<&'a str>,
+    identity: Option<&'a str>,
+}
+
+impl<'a> VerifyKey<'a> {
+    fn from_opts(opts: &'a TextVerifyOpts) -> Self {
+        Self {
+            key: opts.key.as_deref(),
+            namespace: &opts.namespace,
+            allowed_signers: opts.allowed_signers.as_deref(),
+            identity: opts.identity.as_deref(),
         }
-        TextSignFormat::Ed25519 => {
-            todo!()
+    }
+
+    fn key(&self) -> anyhow::Result<&'a str> {
+        self.key
+            .ok_or_else(|| anyhow::anyhow!("需要通过 --key 指定密钥"))
+    }
+}
+
+impl<'a> From<&'a str> for VerifyKey<'a> {
+    fn from(key: &'a str) -> Self {
+        Self {
+            key: Some(key),
+            namespace: DEFAULT_NAMESPACE,
+            allowed_signers: None,
+            identity: None,
         }
-    }?;
+    }
+}
+
+/// 分离的签名文件，记录签名算法、密钥指纹等元数据
+#[derive(Debug, Serialize, Deserialize)]
+struct SignatureDocument {
+    algorithm: TextSignFormat,
+    key_id: String,
+    timestamp: DateTime<Utc>,
+    file_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_length: Option<u64>,
+    /// url-safe base64 编码的签名，SSH签名为 armored 文本
+    signature: String,
+}
 
-    let signed = BASE64_URL_SAFE_NO_PAD.encode(&signed);
+/// 密钥指纹：算法名和密钥的 blake3 哈希的前8个字节。对称密钥也不会因此泄露，
+/// 同一份密钥用于不同算法时指纹也不同
+fn fingerprint(format: TextSignFormat, key: &[u8]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(Into::<&'static str>::into(format).as_bytes());
+    hasher.update(b":");
+    hasher.update(key);
+    hasher.finalize().to_hex()[..16].to_string()
+}
 
-    println!("{}", signed);
+fn process_text_generate(format: TextSignFormat) -> anyhow::Result<Vec<(&'static str, Vec<u8>)>> {
+    match format {
+        TextSignFormat::Blake3 => Blake3::generate(),
+        TextSignFormat::Ed25519 | TextSignFormat::Ed25519ph | TextSignFormat::SshSig => {
+            Ed25519Signer::generate()
+        }
+    }
+}
 
+// 密钥文件只允许当前用户读写，并且不覆盖已有的密钥
+fn write_key_file(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("无法创建密钥文件 {}: {}", path.display(), e))?;
+    file.write_all(content)?;
     Ok(())
 }
 
-fn process_verify(input: &str, data: &str) -> anyhow::Result<()> {
-    todo!()
+fn process_text_encrypt(
+    input: &str,
+    key: &CipherKey,
+    format: Base64Format,
+) -> anyhow::Result<String> {
+    let mut reader = get_reader(input)?;
+    let encrypted = match key {
+        CipherKey::File(path) => ChaCha20::load(path)?.encrypt(&mut reader)?,
+        CipherKey::Password(password) => {
+            if password_strength(password) < MIN_PASSWORD_SCORE {
+                eprintln!("警告: 密码强度过低，建议使用更复杂的密码");
+            }
+            // 密码模式下，密文前面带上派生密钥所需的header
+            let header = PasswordHeader::generate();
+            let cipher = ChaCha20::new(header.derive_key(password)?);
+            let mut ret = header.to_bytes();
+            ret.extend_from_slice(&cipher.encrypt(&mut reader)?);
+            ret
+        }
+    };
+    let encrypted = match format {
+        Base64Format::Standard => BASE64_STANDARD.encode(&encrypted),
+        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.encode(&encrypted),
+    };
+    Ok(encrypted)
+}
+
+fn process_text_decrypt(
+    input: &str,
+    key: &CipherKey,
+    format: Base64Format,
+) -> anyhow::Result<Vec<u8>> {
+    let mut reader = get_reader(input)?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    let encrypted = match format {
+        Base64Format::Standard => BASE64_STANDARD.decode(buf.trim()),
+        Base64Format::UrlSafe => BASE64_URL_SAFE_NO_PAD.decode(buf.trim()),
+    }?;
+    match key {
+        CipherKey::File(path) => ChaCha20::load(path)?.decrypt(&encrypted),
+        CipherKey::Password(password) => {
+            let (header, encrypted) = PasswordHeader::parse(&encrypted)?;
+            let cipher = ChaCha20::new(header.derive_key(password)?);
+            cipher.decrypt(encrypted)
+        }
+    }
+}
+
+/// 加解密时使用的密钥来源
+enum CipherKey {
+    /// 密钥文件路径
+    File(String),
+    /// 通过Argon2id派生密钥的密码
+    Password(String),
+}
+
+impl CipherKey {
+    fn from_opts(
+        key: Option<&str>,
+        password: bool,
+        password_env: Option<&str>,
+        confirm: bool,
+    ) -> anyhow::Result<Self> {
+        if let Some(name) = password_env {
+            let password =
+                std::env::var(name).map_err(|_| anyhow::anyhow!("无法读取环境变量: {}", name))?;
+            return Ok(Self::Password(password));
+        }
+        if password {
+            let password = rpassword::prompt_password("请输入密码: ")?;
+            if confirm && rpassword::prompt_password("请再次输入密码: ")? != password {
+                anyhow::bail!("两次输入的密码不一致");
+            }
+            return Ok(Self::Password(password));
+        }
+        match key {
+            Some(key) => Ok(Self::File(key.to_string())),
+            None => anyhow::bail!("需要指定密钥文件或密码"),
+        }
+    }
+}
+
+/// 密码模式下密文的header: magic | m_cost | t_cost | p_cost | salt
+struct PasswordHeader {
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: [u8; SALT_LEN],
+}
+
+impl PasswordHeader {
+    const LEN: usize = PASSWORD_MAGIC.len() + 12 + SALT_LEN;
+    /// header来自密文，参数不可信，超过默认值4倍的拒绝处理，避免被构造的密文耗尽内存或CPU
+    const MAX_COST_FACTOR: u32 = 4;
+
+    fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::rng().fill(&mut salt);
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+            salt,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(Self::LEN);
+        ret.extend_from_slice(PASSWORD_MAGIC);
+        ret.extend_from_slice(&self.m_cost.to_be_bytes());
+        ret.extend_from_slice(&self.t_cost.to_be_bytes());
+        ret.extend_from_slice(&self.p_cost.to_be_bytes());
+        ret.extend_from_slice(&self.salt);
+        ret
+    }
+
+    /// 解析header，返回header和剩余的密文
+    fn parse(data: &[u8]) -> anyhow::Result<(Self, &[u8])> {
+        if data.len() < Self::LEN || !data.starts_with(PASSWORD_MAGIC) {
+            anyhow::bail!("密文不是使用密码加密的");
+        }
+        let (header, rest) = data.split_at(Self::LEN);
+        let header = &header[PASSWORD_MAGIC.len()..];
+        let u32_at = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+        let header = Self {
+            m_cost: u32_at(0),
+            t_cost: u32_at(4),
+            p_cost: u32_at(8),
+            salt: header[12..].try_into()?,
+        };
+        if header.m_cost > Params::DEFAULT_M_COST * Self::MAX_COST_FACTOR
+            || header.t_cost > Params::DEFAULT_T_COST * Self::MAX_COST_FACTOR
+            || header.p_cost > Params::DEFAULT_P_COST * Self::MAX_COST_FACTOR
+        {
+            anyhow::bail!(
+                "密文中的Argon2参数过大: m_cost={}, t_cost={}, p_cost={}",
+                header.m_cost,
+                header.t_cost,
+                header.p_cost
+            );
+        }
+        Ok((header, rest))
+    }
+
+    fn derive_key(&self, password: &str) -> anyhow::Result<[u8; 32]> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| anyhow::anyhow!("无效的Argon2参数: {}", e))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+        let mut key = [0u8; 32];
+        argon2
+            .hash_password_into(password.as_bytes(), &self.salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("密钥派生失败: {}", e))?;
+        Ok(key)
+    }
 }
 
 trait TextSign {
     fn sign(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>>;
+
+    /// 密钥指纹，与对应验证密钥的指纹相同
+    fn key_id(&self) -> String;
+
+    /// 对输入文件进行签名，"-" 表示stdin
+    fn sign_input(&self, input: &str) -> anyhow::Result<Vec<u8>> {
+        self.sign(&mut get_reader(input)?)
+    }
 }
 
 trait TextVerify {
     fn verify(&self, reader: &mut dyn Read, sig: &[u8]) -> anyhow::Result<bool>;
+
+    /// 验证密钥的指纹，None 表示密钥由签名本身携带
+    fn key_id(&self) -> Option<String>;
+
+    /// 验证输入文件的签名，"-" 表示stdin
+    fn verify_input(&self, input: &str, sig: &[u8]) -> anyhow::Result<bool> {
+        self.verify(&mut get_reader(input)?, sig)
+    }
 }
 
 trait KeyLoader {
@@ -56,6 +560,19 @@ trait KeyLoader {
         Self: Sized;
 }
 
+trait TextEncrypt {
+    fn encrypt(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>>;
+}
+
+trait TextDecrypt {
+    fn decrypt(&self, data: &[u8]) -> anyhow::Result<Vec<u8>>;
+}
+
+trait KeyGenerator {
+    /// 生成密钥，返回 (文件名, 内容) 列表
+    fn generate() -> anyhow::Result<Vec<(&'static str, Vec<u8>)>>;
+}
+
 struct Blake3 {
     key: [u8; 32],
 }
@@ -66,27 +583,71 @@ impl Blake3 {
     }
 
     fn try_new(key: &[u8]) -> anyhow::Result<Self> {
-        let key = &key[..32];
-        let key = key.try_into()?;
-        let signer = Blake3 { key };
-        Ok(signer)
+        let key = key
+            .get(..32)
+            .ok_or_else(|| anyhow::anyhow!("密钥长度为{}字节，至少需要32字节", key.len()))?;
+        Ok(Self::new(key.try_into()?))
+    }
+
+    /// 增量计算keyed hash，内存占用与输入大小无关
+    fn hash_reader(&self, reader: &mut dyn Read) -> anyhow::Result<blake3::Hash> {
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        hasher.update_reader(reader)?;
+        Ok(hasher.finalize())
+    }
+
+    /// 对于普通文件使用mmap并通过rayon多线程计算hash
+    fn hash_input(&self, input: &str) -> anyhow::Result<blake3::Hash> {
+        if input == "-" {
+            return self.hash_reader(&mut get_reader(input)?);
+        }
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        hasher.update_mmap_rayon(input)?;
+        Ok(hasher.finalize())
+    }
+
+    fn verify_hash(hash: blake3::Hash, sig: &[u8]) -> bool {
+        let Ok(sig) = <[u8; 32]>::try_from(sig) else {
+            return false;
+        };
+        // blake3::Hash 的相等比较是常量时间的，可以避免时序攻击
+        hash == blake3::Hash::from_bytes(sig)
     }
 }
+
 impl TextSign for Blake3 {
     fn sign(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
-        let mut buf = Vec::new();
-        reader.read_to_end(&mut buf)?;
-        Ok(blake3::keyed_hash(&self.key, &buf).as_bytes().to_vec())
+        Ok(self.hash_reader(reader)?.as_bytes().to_vec())
+    }
+
+    fn sign_input(&self, input: &str) -> anyhow::Result<Vec<u8>> {
+        Ok(self.hash_input(input)?.as_bytes().to_vec())
+    }
+
+    fn key_id(&self) -> String {
+        fingerprint(TextSignFormat::Blake3, &self.key)
     }
 }
 
 impl TextVerify for Blake3 {
     fn verify(&self, reader: &mut dyn Read, sig: &[u8]) -> anyhow::Result<bool> {
-        let mut buf = Vec::new();
-        reader.read_to_end(&mut buf)?;
-        let binding = blake3::hash(&buf);
-        let hash = binding.as_bytes();
-        Ok(hash == sig)
+        Ok(Self::verify_hash(self.hash_reader(reader)?, sig))
+    }
+
+    fn verify_input(&self, input: &str, sig: &[u8]) -> anyhow::Result<bool> {
+        Ok(Self::verify_hash(self.hash_input(input)?, sig))
+    }
+
+    fn key_id(&self) -> Option<String> {
+        Some(fingerprint(TextSignFormat::Blake3, &self.key))
+    }
+}
+
+impl KeyGenerator for Blake3 {
+    fn generate() -> anyhow::Result<Vec<(&'static str, Vec<u8>)>> {
+        let mut key = [0u8; 32];
+        rand::rng().fill(&mut key);
+        Ok(vec![("blake3.txt", key.to_vec())])
     }
 }
 
@@ -96,7 +657,7 @@ impl KeyLoader for Blake3 {
         Self: Sized,
     {
         let key = fs::read(path)?;
-        Self::try_new(&key[..32])
+        Self::try_new(&key)
     }
 }
 
@@ -106,11 +667,16 @@ struct Ed25519Signer {
 
 impl TextSign for Ed25519Signer {
     fn sign(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+        // 普通Ed25519签名需要完整的消息，大文件请使用 ed25519ph
         let mut buf = Vec::new();
         reader.read_to_end(&mut buf)?;
         let sig = self.key.sign(&buf);
         Ok(sig.to_bytes().to_vec())
     }
+
+    fn key_id(&self) -> String {
+        fingerprint(TextSignFormat::Ed25519, self.key.verifying_key().as_bytes())
+    }
 }
 
 impl KeyLoader for Ed25519Signer {
@@ -119,7 +685,20 @@ impl KeyLoader for Ed25519Signer {
         Self: Sized,
     {
         let key = fs::read(path)?;
-        Self::try_new(&key[..32])
+        Self::try_new(&key)
+    }
+}
+
+impl KeyGenerator for Ed25519Signer {
+    fn generate() -> anyhow::Result<Vec<(&'static str, Vec<u8>)>> {
+        let mut secret = [0u8; 32];
+        rand::rng().fill(&mut secret);
+        let sk = SigningKey::from_bytes(&secret);
+        let pk = sk.verifying_key();
+        Ok(vec![
+            ("ed25519.sk", sk.to_bytes().to_vec()),
+            ("ed25519.pk", pk.to_bytes().to_vec()),
+        ])
     }
 }
 
@@ -129,8 +708,8 @@ impl Ed25519Signer {
     }
 
     fn try_new(key: &[u8]) -> anyhow::Result<Self> {
-        let key = SigningKey::try_from(key)?;
-        Ok(Self { key })
+        let key = keys::parse_signing_key(key)?;
+        Ok(Self::new(key))
     }
 }
 
@@ -144,6 +723,10 @@ impl TextVerify for Ed25519Verifier {
         let sig = Signature::from_bytes(sig.try_into()?);
         Ok(self.key.verify(&buf, &sig).is_ok())
     }
+
+    fn key_id(&self) -> Option<String> {
+        Some(fingerprint(TextSignFormat::Ed25519, self.key.as_bytes()))
+    }
 }
 
 impl KeyLoader for Ed25519Verifier {
@@ -159,7 +742,298 @@ impl Ed25519Verifier {
     }
 
     fn try_new(key: &[u8]) -> anyhow::Result<Self> {
-        let key = VerifyingKey::try_from(key)?;
-        Ok(Self { key })
+        let key = keys::parse_verifying_key(key)?;
+        Ok(Self::new(key))
+    }
+}
+
+/// Ed25519ph(RFC 8032)：先对消息做SHA-512预哈希再签名，适合对大文件签名
+struct Ed25519ph<T>(T);
+
+impl<T: KeyLoader> KeyLoader for Ed25519ph<T> {
+    fn load(path: &str) -> anyhow::Result<Self> {
+        Ok(Self(T::load(path)?))
+    }
+}
+
+impl TextSign for Ed25519ph<Ed25519Signer> {
+    fn sign(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+        let sig = self.0.key.sign_prehashed(prehash(reader)?, None)?;
+        Ok(sig.to_bytes().to_vec())
+    }
+
+    fn key_id(&self) -> String {
+        fingerprint(
+            TextSignFormat::Ed25519ph,
+            self.0.key.verifying_key().as_bytes(),
+        )
+    }
+}
+
+impl TextVerify for Ed25519ph<Ed25519Verifier> {
+    fn verify(&self, reader: &mut dyn Read, sig: &[u8]) -> anyhow::Result<bool> {
+        let sig = Signature::from_bytes(sig.try_into()?);
+        let digest = prehash(reader)?;
+        Ok(self.0.key.verify_prehashed(digest, None, &sig).is_ok())
+    }
+
+    fn key_id(&self) -> Option<String> {
+        Some(fingerprint(
+            TextSignFormat::Ed25519ph,
+            self.0.key.as_bytes(),
+        ))
+    }
+}
+
+/// 分块读取输入计算SHA-512
+fn prehash(reader: &mut dyn Read) -> anyhow::Result<Sha512> {
+    let mut digest = Sha512::new();
+    read_chunks(reader, |buf| digest.update(buf))?;
+    Ok(digest)
+}
+
+struct ChaCha20 {
+    key: [u8; 32],
+}
+
+impl ChaCha20 {
+    fn new(key: [u8; 32]) -> Self {
+        Self { key }
+    }
+
+    fn try_new(key: &[u8]) -> anyhow::Result<Self> {
+        let key = key
+            .get(..32)
+            .ok_or_else(|| anyhow::anyhow!("密钥长度为{}字节，至少需要32字节", key.len()))?;
+        Ok(Self::new(key.try_into()?))
+    }
+}
+
+impl TextEncrypt for ChaCha20 {
+    fn encrypt(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+
+        // 每次加密都使用随机的nonce，并放在密文的最前面
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill(&mut nonce);
+        let cipher = ChaCha20Poly1305::new(&Key::from(self.key));
+        let encrypted = cipher
+            .encrypt(&Nonce::from(nonce), buf.as_slice())
+            .map_err(|_| anyhow::anyhow!("加密失败"))?;
+
+        let mut ret = Vec::with_capacity(NONCE_LEN + encrypted.len());
+        ret.extend_from_slice(&nonce);
+        ret.extend_from_slice(&encrypted);
+        Ok(ret)
+    }
+}
+
+impl TextDecrypt for ChaCha20 {
+    fn decrypt(&self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            anyhow::bail!("密文长度不正确");
+        }
+        let (nonce, encrypted) = data.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into()?;
+        let cipher = ChaCha20Poly1305::new(&Key::from(self.key));
+        cipher
+            .decrypt(&Nonce::from(nonce), encrypted)
+            .map_err(|_| anyhow::anyhow!("解密失败，密钥错误或密文已被篡改"))
+    }
+}
+
+impl KeyLoader for ChaCha20 {
+    fn load(path: &str) -> anyhow::Result<Self> {
+        let key = fs::read(path)?;
+        Self::try_new(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "assets/blake3.txt";
+    const INPUT: &str = "assets/people-100.csv";
+
+    #[test]
+    fn test_blake3_sign_verify() -> anyhow::Result<()> {
+        let signer = Blake3::load(KEY)?;
+        let sig = signer.sign(&mut get_reader(INPUT)?)?;
+
+        let verifier = Blake3::load(KEY)?;
+        assert!(verifier.verify(&mut get_reader(INPUT)?, &sig)?);
+        assert!(!verifier.verify(&mut get_reader(KEY)?, &sig)?);
+        assert!(!verifier.verify(&mut get_reader(INPUT)?, &sig[..31])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_text_verify_blake3() -> anyhow::Result<()> {
+        let signer = Blake3::load(KEY)?;
+        let sig = signer.sign(&mut get_reader(INPUT)?)?;
+        let sig = BASE64_URL_SAFE_NO_PAD.encode(sig);
+
+        let key = KEY.into();
+        assert!(process_text_verify(
+            INPUT,
+            &key,
+            &sig,
+            TextSignFormat::Blake3
+        )?);
+        assert!(!process_text_verify(
+            KEY,
+            &key,
+            &sig,
+            TextSignFormat::Blake3
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_document_verify() -> anyhow::Result<()> {
+        let document = process_text_sign(INPUT, KEY, TextSignFormat::Ed25519ph, DEFAULT_NAMESPACE)?;
+        assert_eq!(document.file_name, "people-100.csv");
+        assert_eq!(document.file_length, Some(fs::metadata(INPUT)?.len()));
+
+        let verifying_key = crate::utils::temp_path("document_verify.pk");
+        let signer = Ed25519Signer::load(KEY)?;
+        fs::write(&verifying_key, signer.key.verifying_key().as_bytes())?;
+        let verifying_key = verifying_key.to_str().unwrap();
+
+        let sig_file = crate::utils::temp_path("document_verify.json");
+        fs::write(&sig_file, serde_json::to_string(&document)?)?;
+        let sig_file = sig_file.to_str().unwrap();
+
+        let key = verifying_key.into();
+        let format = TextSignFormat::Ed25519ph;
+        assert!(process_text_verify_document(INPUT, &key, sig_file, format)?);
+        // 文件长度不一致时直接失败
+        assert!(!process_text_verify_document(KEY, &key, sig_file, format)?);
+        // 签名文件中记录的密钥指纹与提供的密钥不一致
+        let other_key = crate::utils::temp_path("document_verify_other.pk");
+        fs::write(&other_key, &Ed25519Signer::generate()?[1].1)?;
+        let other_key = other_key.to_str().unwrap().into();
+        let err = process_text_verify_document(INPUT, &other_key, sig_file, format).unwrap_err();
+        assert!(err.to_string().contains("不一致"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_document_rejects_algorithm_substitution() -> anyhow::Result<()> {
+        // 只有公钥的攻击者把公钥当作blake3密钥生成签名文件
+        let public_key = crate::utils::temp_path("document_forged.pk");
+        let signer = Ed25519Signer::load(KEY)?;
+        fs::write(&public_key, signer.key.verifying_key().as_bytes())?;
+        let public_key = public_key.to_str().unwrap();
+        let document =
+            process_text_sign(INPUT, public_key, TextSignFormat::Blake3, DEFAULT_NAMESPACE)?;
+
+        let sig_file = crate::utils::temp_path("document_forged.json");
+        fs::write(&sig_file, serde_json::to_string(&document)?)?;
+        let sig_file = sig_file.to_str().unwrap();
+
+        let key = public_key.into();
+        let err = process_text_verify_document(INPUT, &key, sig_file, TextSignFormat::Ed25519)
+            .unwrap_err();
+        assert!(err.to_string().contains("算法"));
+        // 同一份密钥在不同算法下的指纹不同
+        let public_key = signer.key.verifying_key().to_bytes();
+        assert_ne!(
+            fingerprint(TextSignFormat::Blake3, &public_key),
+            fingerprint(TextSignFormat::Ed25519, &public_key)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_chacha20_encrypt_decrypt() -> anyhow::Result<()> {
+        let cipher = ChaCha20::load(KEY)?;
+        let data = b"hello world";
+        let encrypted = cipher.encrypt(&mut &data[..])?;
+        assert_eq!(encrypted.len(), NONCE_LEN + data.len() + 16);
+        assert_eq!(cipher.decrypt(&encrypted)?, data);
+
+        let mut tampered = encrypted.clone();
+        tampered[NONCE_LEN] ^= 1;
+        assert!(cipher.decrypt(&tampered).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_password_encrypt_decrypt() -> anyhow::Result<()> {
+        let key = CipherKey::Password("correct horse battery staple".to_string());
+        let encrypted = process_text_encrypt(INPUT, &key, Base64Format::UrlSafe)?;
+        let encrypted_file = crate::utils::temp_path("password_encrypt.txt");
+        fs::write(&encrypted_file, encrypted)?;
+        let encrypted_file = encrypted_file.to_str().unwrap();
+
+        let decrypted = process_text_decrypt(encrypted_file, &key, Base64Format::UrlSafe)?;
+        assert_eq!(decrypted, fs::read(INPUT)?);
+
+        let wrong = CipherKey::Password("wrong password".to_string());
+        assert!(process_text_decrypt(encrypted_file, &wrong, Base64Format::UrlSafe).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_password_header_rejects_huge_cost() {
+        let mut header = PasswordHeader::generate();
+        assert!(PasswordHeader::parse(&header.to_bytes()).is_ok());
+
+        header.m_cost = u32::MAX;
+        assert!(PasswordHeader::parse(&header.to_bytes()).is_err());
+
+        let mut header = PasswordHeader::generate();
+        header.t_cost = u32::MAX;
+        assert!(PasswordHeader::parse(&header.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_generate_sign_verify() -> anyhow::Result<()> {
+        let keys = Ed25519Signer::generate()?;
+        let signer = Ed25519Signer::try_new(&keys[0].1)?;
+        let verifier = Ed25519Verifier::try_new(&keys[1].1)?;
+
+        let data = b"hello world";
+        let sig = signer.sign(&mut &data[..])?;
+        assert!(verifier.verify(&mut &data[..], &sig)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blake3_sign_input_matches_reader() -> anyhow::Result<()> {
+        let signer = Blake3::load(KEY)?;
+        let by_reader = signer.sign(&mut get_reader(INPUT)?)?;
+        let by_input = signer.sign_input(INPUT)?;
+        assert_eq!(by_reader, by_input);
+        assert!(signer.verify_input(INPUT, &by_reader)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ed25519ph_sign_verify() -> anyhow::Result<()> {
+        let signer = Ed25519ph::<Ed25519Signer>::load(KEY)?;
+        let verifier = Ed25519ph(Ed25519Verifier::new(signer.0.key.verifying_key()));
+
+        let sig = signer.sign_input(INPUT)?;
+        assert!(verifier.verify_input(INPUT, &sig)?);
+        assert!(!verifier.verify_input(KEY, &sig)?);
+        // 预哈希签名和普通Ed25519签名不能混用
+        assert!(!verifier.0.verify_input(INPUT, &sig)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ed25519_sign_verify() -> anyhow::Result<()> {
+        let signer = Ed25519Signer::load(KEY)?;
+        let verifier = Ed25519Verifier::new(signer.key.verifying_key());
+
+        let data = b"hello world";
+        let sig = signer.sign(&mut &data[..])?;
+        assert!(verifier.verify(&mut &data[..], &sig)?);
+        assert!(!verifier.verify(&mut &b"hello rust"[..], &sig)?);
+        Ok(())
     }
 }
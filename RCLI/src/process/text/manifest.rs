@@ -0,0 +1,224 @@
+use super::{VerifyKey, decode_signature, encode_signature, load_signer, load_verifier};
+use crate::opts::text::TextSignFormat;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// 目录清单：记录目录下每个文件的 blake3 哈希，并对整个文件列表签名
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct Manifest {
+    algorithm: TextSignFormat,
+    key_id: String,
+    timestamp: DateTime<Utc>,
+    /// 相对路径 -> 十六进制 blake3 哈希，BTreeMap 保证序列化顺序稳定
+    files: BTreeMap<String, String>,
+    /// 签名内容为除 signature 外整个清单的 json 序列化结果，编码方式与 SignatureDocument 相同
+    signature: String,
+}
+
+/// 清单中被签名的部分，字段顺序固定，files 按路径排序，保证序列化结果唯一
+#[derive(Serialize)]
+struct SignedManifest<'a> {
+    algorithm: TextSignFormat,
+    key_id: &'a str,
+    timestamp: &'a DateTime<Utc>,
+    files: &'a BTreeMap<String, String>,
+}
+
+impl Manifest {
+    fn signed_content(&self) -> anyhow::Result<Vec<u8>> {
+        let signed = SignedManifest {
+            algorithm: self.algorithm,
+            key_id: &self.key_id,
+            timestamp: &self.timestamp,
+            files: &self.files,
+        };
+        Ok(serde_json::to_vec(&signed)?)
+    }
+}
+
+/// 目录与清单的差异
+#[derive(Debug, Default)]
+pub(super) struct ManifestReport {
+    pub added: Vec<String>,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl ManifestReport {
+    pub fn is_clean(&self) -> bool {
+        self.added.is_empty() && self.missing.is_empty() && self.modified.is_empty()
+    }
+}
+
+pub(super) fn process_text_sign_manifest(
+    dir: &Path,
+    key: &str,
+    format: TextSignFormat,
+    namespace: &str,
+    exclude: Option<&Path>,
+) -> anyhow::Result<Manifest> {
+    let files = hash_dir(dir, exclude)?;
+    let signer = load_signer(key, format, namespace)?;
+    let mut manifest = Manifest {
+        algorithm: format,
+        key_id: signer.key_id(),
+        timestamp: Utc::now(),
+        files,
+        signature: String::new(),
+    };
+    let signed = signer.sign(&mut manifest.signed_content()?.as_slice())?;
+    manifest.signature = encode_signature(format, &signed);
+    Ok(manifest)
+}
+
+/// 与签名文件一样，算法由调用方指定，清单中记录的算法必须与之一致
+pub(super) fn process_text_verify_manifest(
+    dir: &Path,
+    key: &VerifyKey,
+    sig_file: &str,
+    format: TextSignFormat,
+) -> anyhow::Result<ManifestReport> {
+    let manifest: Manifest = serde_json::from_slice(&fs::read(sig_file)?)?;
+    if manifest.algorithm != format {
+        anyhow::bail!(
+            "清单的算法({})与指定的算法({})不一致",
+            manifest.algorithm,
+            format
+        );
+    }
+
+    let verifier = load_verifier(key, format)?;
+    if let Some(key_id) = verifier.key_id()
+        && key_id != manifest.key_id
+    {
+        anyhow::bail!(
+            "清单使用的密钥({})与提供的密钥({})不一致",
+            manifest.key_id,
+            key_id
+        );
+    }
+    let sig = decode_signature(format, &manifest.signature)?;
+    if !verifier.verify(&mut manifest.signed_content()?.as_slice(), &sig)? {
+        anyhow::bail!("清单签名验证失败");
+    }
+
+    // 清单文件本身可能就放在目录中，比较时需要排除
+    let actual = hash_dir(dir, Some(Path::new(sig_file)))?;
+    let mut report = ManifestReport::default();
+    for (path, hash) in &manifest.files {
+        match actual.get(path) {
+            None => report.missing.push(path.clone()),
+            Some(actual_hash) if actual_hash != hash => report.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    report.added = actual
+        .into_keys()
+        .filter(|path| !manifest.files.contains_key(path))
+        .collect();
+    Ok(report)
+}
+
+/// 遍历目录计算每个文件的哈希，key 为使用 `/` 分隔的相对路径
+fn hash_dir(dir: &Path, exclude: Option<&Path>) -> anyhow::Result<BTreeMap<String, String>> {
+    let exclude = exclude.and_then(|path| path.canonicalize().ok());
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if exclude.is_some() && entry.path().canonicalize().ok() == exclude {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(dir)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut hasher = blake3::Hasher::new();
+        hasher.update_mmap_rayon(entry.path())?;
+        files.insert(relative, hasher.finalize().to_hex().to_string());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "assets/blake3.txt";
+    const FORMAT: TextSignFormat = TextSignFormat::Blake3;
+
+    #[test]
+    fn test_manifest_sign_verify() -> anyhow::Result<()> {
+        let dir = crate::utils::temp_path("manifest_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::write(dir.join("a.txt"), "a")?;
+        fs::write(dir.join("sub/b.txt"), "b")?;
+        fs::write(dir.join("c.txt"), "c")?;
+
+        let manifest = process_text_sign_manifest(&dir, KEY, FORMAT, "file", None)?;
+        assert_eq!(manifest.files.len(), 3);
+        assert!(manifest.files.contains_key("sub/b.txt"));
+
+        let sig_file = dir.join("manifest.json");
+        fs::write(&sig_file, serde_json::to_string(&manifest)?)?;
+        let sig_file = sig_file.to_str().unwrap();
+        assert!(process_text_verify_manifest(&dir, &KEY.into(), sig_file, FORMAT)?.is_clean());
+
+        fs::write(dir.join("a.txt"), "changed")?;
+        fs::remove_file(dir.join("sub/b.txt"))?;
+        fs::write(dir.join("d.txt"), "d")?;
+        let report = process_text_verify_manifest(&dir, &KEY.into(), sig_file, FORMAT)?;
+        assert_eq!(report.modified, vec!["a.txt"]);
+        assert_eq!(report.missing, vec!["sub/b.txt"]);
+        assert_eq!(report.added, vec!["d.txt"]);
+
+        // 篡改清单后签名验证失败
+        let tampered = fs::read_to_string(sig_file)?.replace("sub/b.txt", "sub/x.txt");
+        fs::write(sig_file, tampered)?;
+        assert!(process_text_verify_manifest(&dir, &KEY.into(), sig_file, FORMAT).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_manifest_metadata_is_signed() -> anyhow::Result<()> {
+        let dir = crate::utils::temp_path("manifest_metadata_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("a.txt"), "a")?;
+
+        let manifest = process_text_sign_manifest(&dir, KEY, FORMAT, "file", None)?;
+        let sig_file = dir.join("manifest.json");
+        let sig_file = sig_file.to_str().unwrap();
+        let verify = |manifest: &Manifest| -> anyhow::Result<ManifestReport> {
+            fs::write(sig_file, serde_json::to_string(manifest)?)?;
+            process_text_verify_manifest(&dir, &KEY.into(), sig_file, FORMAT)
+        };
+        assert!(verify(&manifest)?.is_clean());
+
+        // 修改时间戳后签名不再匹配
+        let mut tampered: Manifest = serde_json::from_str(&serde_json::to_string(&manifest)?)?;
+        tampered.timestamp += chrono::Duration::days(1);
+        assert!(verify(&tampered).is_err());
+
+        // 修改算法后与 --format 不一致
+        let mut tampered: Manifest = serde_json::from_str(&serde_json::to_string(&manifest)?)?;
+        tampered.algorithm = TextSignFormat::Ed25519;
+        assert!(verify(&tampered).is_err());
+
+        // 修改密钥指纹后签名不再匹配
+        let mut tampered = manifest;
+        tampered.key_id = "0000000000000000".to_string();
+        assert!(verify(&tampered).is_err());
+        Ok(())
+    }
+}
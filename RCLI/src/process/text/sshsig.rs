@@ -0,0 +1,373 @@
+use super::{Ed25519Signer, Ed25519Verifier, TextSign, TextVerify, fingerprint};
+use crate::opts::text::TextSignFormat;
+use crate::utils::read_chunks;
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256, Sha512};
+use ssh_key::public::{Ed25519PublicKey, KeyData};
+use ssh_key::{Algorithm, HashAlg, LineEnding, SshSig};
+use std::fs;
+use std::io::Read;
+
+pub(super) const SSHSIG_PEM: &str = "-----BEGIN SSH SIGNATURE-----";
+const MAGIC_PREAMBLE: &[u8] = b"SSHSIG";
+
+/// 生成与 `ssh-keygen -Y sign` 兼容的 armored SSHSIG 签名
+pub(super) struct SshSigSigner {
+    signer: Ed25519Signer,
+    namespace: String,
+}
+
+impl SshSigSigner {
+    pub fn new(signer: Ed25519Signer, namespace: &str) -> Self {
+        Self {
+            signer,
+            namespace: namespace.to_string(),
+        }
+    }
+}
+
+impl TextSign for SshSigSigner {
+    fn sign(&self, reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+        let hash = digest(reader, HashAlg::Sha512)?;
+        let signed_data = signed_data(&self.namespace, &[], HashAlg::Sha512, &hash);
+        let sig = self.signer.key.sign(&signed_data);
+
+        let public_key =
+            KeyData::from(Ed25519PublicKey(self.signer.key.verifying_key().to_bytes()));
+        let sig = ssh_key::Signature::new(Algorithm::Ed25519, sig.to_bytes())?;
+        let sig = SshSig::new(public_key, self.namespace.as_str(), HashAlg::Sha512, sig)?;
+        Ok(sig.to_pem(LineEnding::LF)?.into_bytes())
+    }
+
+    fn key_id(&self) -> String {
+        fingerprint(
+            TextSignFormat::SshSig,
+            self.signer.key.verifying_key().as_bytes(),
+        )
+    }
+}
+
+/// 验证 armored SSHSIG 签名。签名中带有公钥，需要通过 `--key` 或 allowed_signers 确认公钥可信
+pub(super) struct SshSigVerifier {
+    verifier: Option<Ed25519Verifier>,
+    allowed_signers: Option<AllowedSigners>,
+    identity: Option<String>,
+    namespace: String,
+}
+
+impl SshSigVerifier {
+    pub fn new(
+        verifier: Option<Ed25519Verifier>,
+        allowed_signers: Option<&str>,
+        identity: Option<&str>,
+        namespace: &str,
+    ) -> anyhow::Result<Self> {
+        if verifier.is_none() && allowed_signers.is_none() {
+            anyhow::bail!("验证SSH签名需要指定 --key 或 --allowed-signers");
+        }
+        let allowed_signers = allowed_signers.map(AllowedSigners::load).transpose()?;
+        Ok(Self {
+            verifier,
+            allowed_signers,
+            identity: identity.map(|s| s.to_string()),
+            namespace: namespace.to_string(),
+        })
+    }
+
+    /// 确认签名中的公钥是可信的
+    fn is_trusted(&self, key: &VerifyingKey) -> bool {
+        if let Some(verifier) = &self.verifier
+            && verifier.key != *key
+        {
+            eprintln!("签名公钥与 --key 指定的公钥不一致");
+            return false;
+        }
+        if let Some(allowed_signers) = &self.allowed_signers {
+            match allowed_signers.find(key, self.identity.as_deref(), &self.namespace) {
+                Some(principal) => eprintln!("签名者: {}", principal),
+                None => {
+                    eprintln!("签名公钥不在 allowed_signers 中");
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl TextVerify for SshSigVerifier {
+    fn verify(&self, reader: &mut dyn Read, sig: &[u8]) -> anyhow::Result<bool> {
+        let sig = SshSig::from_pem(sig).map_err(|e| anyhow::anyhow!("无效的SSH签名: {}", e))?;
+        if sig.namespace() != self.namespace {
+            eprintln!(
+                "签名的namespace({})与期望的namespace({})不一致",
+                sig.namespace(),
+                self.namespace
+            );
+            return Ok(false);
+        }
+        let key = sig
+            .public_key()
+            .ed25519()
+            .ok_or_else(|| anyhow::anyhow!("暂不支持的SSH签名算法: {}", sig.algorithm()))?;
+        let key = VerifyingKey::try_from(key)?;
+        if !self.is_trusted(&key) {
+            return Ok(false);
+        }
+
+        let hash = digest(reader, sig.hash_alg())?;
+        let signed_data = signed_data(sig.namespace(), sig.reserved(), sig.hash_alg(), &hash);
+        let signature = Signature::from_slice(sig.signature_bytes())?;
+        Ok(key.verify(&signed_data, &signature).is_ok())
+    }
+
+    fn key_id(&self) -> Option<String> {
+        // 只有 allowed_signers 时，公钥由签名本身决定
+        self.verifier
+            .as_ref()
+            .map(|verifier| fingerprint(TextSignFormat::SshSig, verifier.key.as_bytes()))
+    }
+}
+
+/// 分块计算消息的哈希，SSHSIG 签名的是消息的哈希而不是消息本身
+fn digest(reader: &mut dyn Read, hash_alg: HashAlg) -> anyhow::Result<Vec<u8>> {
+    match hash_alg {
+        HashAlg::Sha256 => digest_with::<Sha256>(reader),
+        HashAlg::Sha512 => digest_with::<Sha512>(reader),
+        _ => anyhow::bail!("暂不支持的哈希算法: {}", hash_alg),
+    }
+}
+
+fn digest_with<D: Digest>(reader: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+    let mut hasher = D::new();
+    read_chunks(reader, |buf| hasher.update(buf))?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// 被签名的数据: MAGIC_PREAMBLE | string namespace | string reserved | string hash_alg | string H(message)
+fn signed_data(namespace: &str, reserved: &[u8], hash_alg: HashAlg, hash: &[u8]) -> Vec<u8> {
+    let mut data = MAGIC_PREAMBLE.to_vec();
+    for field in [
+        namespace.as_bytes(),
+        reserved,
+        hash_alg.as_str().as_bytes(),
+        hash,
+    ] {
+        data.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        data.extend_from_slice(field);
+    }
+    data
+}
+
+/// ssh-keygen 的 allowed_signers 文件，每行格式为: principals [options] keytype base64-key [comment]
+struct AllowedSigners {
+    entries: Vec<AllowedSigner>,
+}
+
+struct AllowedSigner {
+    principals: Vec<String>,
+    namespaces: Option<Vec<String>>,
+    key: VerifyingKey,
+}
+
+impl AllowedSigners {
+    fn load(path: &str) -> anyhow::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut entries = Vec::new();
+        for (lineno, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (principals, rest) = split_field(line);
+            let (field, rest) = split_field(rest);
+            // options 是可选的，第二个字段如果是公钥类型说明没有options
+            let (options, key_type, rest) = if is_key_type(field) {
+                ("", field, rest)
+            } else {
+                let (key_type, rest) = split_field(rest);
+                (field, key_type, rest)
+            };
+            let options = parse_options(options);
+            if options.iter().any(|(name, _)| name == "cert-authority") {
+                // 不支持证书，忽略该行
+                continue;
+            }
+            let (key, _comment) = split_field(rest);
+            if key_type != "ssh-ed25519" {
+                continue;
+            }
+            let key = ssh_key::PublicKey::from_openssh(&format!("{} {}", key_type, key)).map_err(
+                |e| anyhow::anyhow!("allowed_signers 第{}行公钥无效: {}", lineno + 1, e),
+            )?;
+            let key = key
+                .key_data()
+                .ed25519()
+                .ok_or_else(|| anyhow::anyhow!("allowed_signers 第{}行公钥无效", lineno + 1))?;
+            let namespaces = options
+                .iter()
+                .find(|(name, _)| name == "namespaces")
+                .map(|(_, value)| value.split(',').map(|s| s.trim().to_string()).collect());
+            entries.push(AllowedSigner {
+                principals: principals.split(',').map(|s| s.to_string()).collect(),
+                namespaces,
+                key: VerifyingKey::try_from(key)?,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// 查找允许使用该公钥在namespace下签名的principal
+    fn find(&self, key: &VerifyingKey, identity: Option<&str>, namespace: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.key == *key)
+            .filter(|entry| {
+                entry.namespaces.as_ref().is_none_or(|namespaces| {
+                    namespaces.iter().any(|n| wildcard_match(n, namespace))
+                })
+            })
+            .find_map(|entry| match identity {
+                Some(identity) => entry
+                    .principals
+                    .iter()
+                    .any(|p| wildcard_match(p, identity))
+                    .then(|| identity.to_string()),
+                None => Some(entry.principals.join(",")),
+            })
+    }
+}
+
+fn is_key_type(field: &str) -> bool {
+    ["ssh-", "ecdsa-", "sk-"]
+        .iter()
+        .any(|prefix| field.starts_with(prefix))
+}
+
+/// 按空白分割出第一个字段，双引号中的空白不分割
+fn split_field(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let mut in_quotes = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => return (&s[..i], s[i..].trim_start()),
+            _ => {}
+        }
+    }
+    (s, "")
+}
+
+/// 解析 `name[="value"],...` 格式的options
+fn parse_options(s: &str) -> Vec<(String, String)> {
+    let mut options = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in s.chars().chain(std::iter::once(',')) {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                if !current.is_empty() {
+                    let (name, value) = current.split_once('=').unwrap_or((&current, ""));
+                    options.push((name.to_lowercase(), value.to_string()));
+                }
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+    options
+}
+
+/// 支持 `*` 和 `?` 通配符的匹配
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    let (p, s): (Vec<char>, Vec<char>) = (pattern.chars().collect(), s.chars().collect());
+    let (mut pi, mut si) = (0, 0);
+    let (mut star, mut mark) = (None, 0);
+    while si < s.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == s[si]) {
+            pi += 1;
+            si += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some(pi);
+            mark = si;
+            pi += 1;
+        } else if let Some(star) = star {
+            pi = star + 1;
+            mark += 1;
+            si = mark;
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::text::KeyLoader;
+
+    const KEY: &str = "assets/blake3.txt";
+
+    fn public_key_line(signer: &Ed25519Signer) -> anyhow::Result<String> {
+        let key = KeyData::from(Ed25519PublicKey(signer.key.verifying_key().to_bytes()));
+        Ok(ssh_key::PublicKey::from(key).to_openssh()?)
+    }
+
+    #[test]
+    fn test_sshsig_sign_verify() -> anyhow::Result<()> {
+        let signer = SshSigSigner::new(Ed25519Signer::load(KEY)?, "file");
+        let sig = signer.sign(&mut &b"hello world"[..])?;
+        assert!(String::from_utf8(sig.clone())?.starts_with(SSHSIG_PEM));
+
+        let key = Ed25519Verifier::new(signer.signer.key.verifying_key());
+        let verifier = SshSigVerifier::new(Some(key), None, None, "file")?;
+        assert!(verifier.verify(&mut &b"hello world"[..], &sig)?);
+        assert!(!verifier.verify(&mut &b"hello rust"[..], &sig)?);
+
+        let key = Ed25519Verifier::new(signer.signer.key.verifying_key());
+        let verifier = SshSigVerifier::new(Some(key), None, None, "git")?;
+        assert!(!verifier.verify(&mut &b"hello world"[..], &sig)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_allowed_signers() -> anyhow::Result<()> {
+        let signer = Ed25519Signer::load(KEY)?;
+        let line = public_key_line(&signer)?;
+        let content = format!(
+            "# comment\n*@example.com namespaces=\"git,file\" {} alice\nbob {}\n",
+            line, line
+        );
+        let allowed_signers = AllowedSigners::parse(&content)?;
+        assert_eq!(allowed_signers.entries.len(), 2);
+
+        let key = signer.key.verifying_key();
+        assert_eq!(
+            allowed_signers.find(&key, Some("alice@example.com"), "file"),
+            Some("alice@example.com".to_string())
+        );
+        assert_eq!(
+            allowed_signers.find(&key, Some("alice@example.com"), "other"),
+            None
+        );
+        assert_eq!(
+            allowed_signers.find(&key, Some("bob"), "other"),
+            Some("bob".to_string())
+        );
+        assert_eq!(allowed_signers.find(&key, Some("carol"), "file"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*@example.com", "alice@example.com"));
+        assert!(wildcard_match("a?c", "abc"));
+        assert!(!wildcard_match("a?c", "abbc"));
+        assert!(!wildcard_match("*@example.com", "alice@example.org"));
+    }
+}
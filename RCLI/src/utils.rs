@@ -1,5 +1,8 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const CHUNK_SIZE: usize = 64 * 1024;
 
 pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     let reader: Box<dyn Read> = if input == "-" {
@@ -10,4 +13,32 @@ pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
         Box::new(File::open(input)?)
     };
     Ok(reader)
-}
\ No newline at end of file
+}
+
+pub fn get_writer(output: Option<&Path>) -> anyhow::Result<Box<dyn Write>> {
+    let writer: Box<dyn Write> = match output {
+        // 这里返回的是文件写入
+        Some(path) => Box::new(File::create(path)?),
+        // 不指定输出文件时写到stdout
+        None => Box::new(std::io::stdout().lock()),
+    };
+    Ok(writer)
+}
+
+/// 按64K分块读取输入直到结束，每块交给f处理，用于哈希、HMAC等增量计算
+pub fn read_chunks(reader: &mut dyn Read, mut f: impl FnMut(&[u8])) -> std::io::Result<()> {
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        f(&buf[..n]);
+    }
+}
+
+/// 测试用的临时文件路径，带上进程号，避免并行执行的测试进程互相覆盖
+#[cfg(test)]
+pub(crate) fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("rcli-{}-{}", std::process::id(), name))
+}